@@ -0,0 +1,103 @@
+//! tmux hooks running an autosave whenever the layout of a session changes.
+use common::args::Args;
+use common::shell::{run_shell, shell_quote};
+use common::tmux::Server;
+use load::tmux;
+use load::tmux::version::{Feature, Version};
+use std::env;
+use std::path::Path;
+
+/// The hooks saving after a change. Closing a session isn't among them, the
+/// last save holding it is what a restore is for.
+static HOOKS: &[&str] = &[
+    "after-new-session",
+    "after-new-window",
+    "after-split-window",
+    "after-rename-window",
+    "after-kill-pane",
+];
+
+/// Set the hooks globally on the server. Setting them again replaces them.
+pub fn install(server: &Server, args: &Args) -> Result<(), String> {
+    if let Some(version) = tmux::version(server) {
+        Feature::new("set-hook after-new-window", Version::new(2, 4, 0)).check(&version)?;
+    };
+
+    let exe = env::current_exe().map_err(|e| format!("We couldn't find muxed itself: {}", e))?;
+    let command = run_shell(&autosave_command(&exe, args));
+
+    for hook in HOOKS {
+        tmux::checked(
+            tmux::call(server, &["set-hook", "-g", hook, &command]),
+            &format!("We couldn't set the {} hook", hook),
+        )?;
+    }
+
+    Ok(())
+}
+
+/// The shell command running a single autosave, with the settings this one
+/// was given.
+fn autosave_command(exe: &Path, args: &Args) -> String {
+    let mut command = vec![
+        shell_quote(&exe.to_string_lossy()),
+        "autosave".to_string(),
+        "--once".to_string(),
+    ];
+
+    let settings = vec![
+        ("-p", &args.flag_p),
+        ("--allow", &args.flag_allow),
+        ("--deny", &args.flag_deny),
+        ("--socket-name", &args.flag_socket_name),
+        ("--socket-path", &args.flag_socket_path),
+        ("--tmux-command", &args.flag_tmux_command),
+        ("--tmux-config", &args.flag_tmux_config),
+    ];
+
+    for (flag, value) in settings {
+        if let Some(value) = value {
+            command.push(flag.to_string());
+            command.push(shell_quote(value));
+        };
+    }
+
+    if let Some(keep) = args.flag_keep {
+        command.push("--keep".to_string());
+        command.push(keep.to_string());
+    };
+
+    command.join(" ")
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn expect_command_with_settings() {
+        let args = Args {
+            flag_socket_name: Some("work".to_string()),
+            flag_tmux_config: Some("work.conf".to_string()),
+            flag_keep: Some(3),
+            ..Default::default()
+        };
+        let command = autosave_command(Path::new("/usr/local/bin/muxed"), &args);
+
+        assert_eq!(
+            command,
+            "/usr/local/bin/muxed autosave --once --socket-name work --tmux-config work.conf --keep 3"
+        )
+    }
+
+    #[test]
+    fn expect_paths_with_spaces_to_be_quoted() {
+        let args = Args {
+            flag_p: Some("/tmp/my projects".to_string()),
+            ..Default::default()
+        };
+        let command = autosave_command(Path::new("/opt/muxed"), &args);
+
+        assert_eq!(command, "/opt/muxed autosave --once -p '/tmp/my projects'")
+    }
+}
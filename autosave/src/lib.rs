@@ -0,0 +1,200 @@
+//! Muxedautosave. Keeps running sessions saved so they can be restored after a
+//! reboot. Saves are regular project files written by snapshot, and restoring
+//! loads them like any other project.
+extern crate common;
+extern crate load;
+extern crate snapshot;
+
+pub mod hooks;
+pub mod state;
+
+use common::args::Args;
+use common::project_paths::project_paths;
+use common::tmux::Server;
+use snapshot::tmux::pane::process::Filter;
+use state::State;
+use std::thread::sleep;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// The folder in the project directory saves are kept in.
+static AUTOSAVE_FOLDER: &str = "autosave";
+
+/// Seconds between saves when nothing else was chosen.
+static INTERVAL: u64 = 300;
+
+/// How many saves are kept when nothing else was chosen.
+static KEEP: usize = 10;
+
+/// The main execution method.
+/// Saves every running session every `--interval` seconds. With `--once` a
+/// single save is made, and with `--hooks` tmux is set up to save whenever a
+/// session changes instead.
+///
+/// # Examples
+///
+/// You can run the command:
+///
+/// ```
+/// $ ./muxed autosave --interval 60 --keep 20
+/// ```
+///
+/// or
+///
+/// ```
+/// $ ./muxed autosave --hooks
+/// ```
+pub fn exec(args: Args) -> Result<(), String> {
+    let server = Server::from(&args);
+
+    if args.flag_hooks {
+        return hooks::install(&server, &args);
+    };
+
+    let state = state(&args);
+    let filter = Filter::new(args.flag_allow.as_deref(), args.flag_deny.as_deref());
+    let keep = args.flag_keep.unwrap_or(KEEP);
+
+    if args.flag_once {
+        return save(&server, &filter, &state, keep);
+    };
+
+    let interval = Duration::from_secs(args.flag_interval.unwrap_or(INTERVAL));
+
+    loop {
+        if let Err(e) = save(&server, &filter, &state, keep) {
+            println!("Muxed ran in to a problem autosaving: {}", e);
+        };
+
+        sleep(interval);
+    }
+}
+
+/// Recreate sessions from the latest save. A named project restores just
+/// that session, otherwise, or with --all, every saved session that isn't
+/// running already is restored in the background.
+///
+/// # Examples
+///
+/// ```
+/// $ ./muxed restore --all
+/// ```
+///
+/// or
+///
+/// ```
+/// $ ./muxed restore my_project
+/// ```
+pub fn restore(args: Args) -> Result<(), String> {
+    let server = Server::from(&args);
+    let state = state(&args);
+    let save = state
+        .latest()
+        .ok_or_else(|| format!("No autosave was found in {}.", state.dir.display()))?;
+    let save_dir = save.display().to_string();
+
+    if !args.flag_all && !args.arg_project.is_empty() {
+        // Sessions are saved under their project name, so open that one.
+        let name = snapshot::project_name(&args.arg_project);
+        if !snapshot::project_file(&save, &name).exists() {
+            return Err(format!("No autosave of {} was found in {}.", args.arg_project, save_dir));
+        };
+
+        return load::exec(Args {
+            arg_project: name,
+            flag_p: Some(save_dir),
+            ..args
+        });
+    };
+
+    for name in state::projects(&save) {
+        if load::tmux::has_session(&server, &name).success() {
+            println!("{} is already running.", name);
+            continue;
+        };
+
+        load::exec(Args {
+            arg_project: name.clone(),
+            flag_d: true,
+            flag_p: Some(save_dir.clone()),
+            ..args.clone()
+        })?;
+        println!("Restored {} \u{1F60A}", name);
+    }
+
+    Ok(())
+}
+
+/// Save every running session, unless nothing changed since the last save.
+fn save(server: &Server, filter: &Filter, state: &State, keep: usize) -> Result<(), String> {
+    let mut files = vec![];
+
+    for name in snapshot::tmux::sessions(server)? {
+        // Sessions can close while we work through the list.
+        if let Ok(session) = snapshot::snapshot(server, &name, filter, None) {
+            let file = snapshot::project_file(&state.dir, &name);
+            let file_name = file.file_name().unwrap().to_string_lossy().to_string();
+            files.push((file_name, session.to_yaml()?));
+        };
+    }
+
+    if files.is_empty() || state.is_latest(&files) {
+        return Ok(());
+    };
+
+    let stamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_err(|e| e.to_string())?
+        .as_millis();
+
+    state.write(stamp, &files)?;
+    state.prune(keep)
+}
+
+fn state(args: &Args) -> State {
+    State::new(project_paths(args).project_directory.join(AUTOSAVE_FOLDER))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use common::rand_names;
+    use std::fs;
+
+    #[test]
+    fn expect_saved_sessions_to_be_restored() {
+        let socket = rand_names::project_file_name();
+        let server = Server::new(None, Some(socket.clone()), None, None);
+        // Restoring on to a server of its own, so it can't race the first
+        // one shutting down.
+        let restore_socket = rand_names::project_file_name();
+        let restore_server = Server::new(None, Some(restore_socket.clone()), None, None);
+        let dir = rand_names::project_path();
+        let args = Args {
+            arg_project: String::new(),
+            flag_p: Some(dir.display().to_string()),
+            flag_socket_name: Some(socket),
+            flag_once: true,
+            ..Default::default()
+        };
+
+        let _ = load::tmux::call(&server, &["new-session", "-d", "-s", "saved", "-n", "editor"]);
+        let saved = exec(args.clone());
+        let _ = load::tmux::call(&server, &["kill-server"]);
+
+        let restored = restore(Args {
+            flag_all: true,
+            flag_socket_name: Some(restore_socket),
+            ..args
+        });
+        let running = load::tmux::has_session(&restore_server, "saved").success();
+        let windows = load::tmux::call(&restore_server, &["list-windows", "-t", "saved", "-F", "#W"]);
+
+        let _ = load::tmux::call(&restore_server, &["kill-server"]);
+        let _ = fs::remove_dir_all(&dir);
+
+        assert_eq!(saved, Ok(()));
+        assert_eq!(restored, Ok(()));
+        assert!(running);
+        assert_eq!(String::from_utf8_lossy(&windows.unwrap().stdout), "editor\n")
+    }
+}
@@ -0,0 +1,184 @@
+//! The autosave state directory. Every save is a directory of project files,
+//! one per session, named after the time it was taken in milliseconds.
+//! ex. `~/.muxed/autosave/1603028400000/work.yml`
+use common::project_paths::CONFIG_EXTENSION;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process;
+use std::str::FromStr;
+
+/// A project file in a save. The name of the file and what's written to it.
+pub type File = (String, String);
+
+pub struct State {
+    pub dir: PathBuf,
+}
+
+impl State {
+    pub fn new(dir: PathBuf) -> State {
+        State { dir }
+    }
+
+    /// The saves in the state directory, oldest first.
+    pub fn saves(&self) -> Vec<PathBuf> {
+        let entries = match fs::read_dir(&self.dir) {
+            Ok(entries) => entries,
+            Err(_) => return vec![],
+        };
+
+        let mut saves: Vec<(u128, PathBuf)> = entries
+            .filter_map(|e| e.ok())
+            .filter_map(|e| {
+                let stamp = e.file_name().to_str().and_then(|n| u128::from_str(n).ok())?;
+                Some((stamp, e.path()))
+            })
+            .collect();
+
+        saves.sort();
+        saves.into_iter().map(|(_, path)| path).collect()
+    }
+
+    pub fn latest(&self) -> Option<PathBuf> {
+        self.saves().pop()
+    }
+
+    /// Whether the latest save holds exactly these files.
+    pub fn is_latest(&self, files: &[File]) -> bool {
+        let latest = match self.latest() {
+            Some(latest) => latest,
+            None => return false,
+        };
+
+        projects(&latest).len() == files.len()
+            && files.iter().all(|(name, contents)| {
+                fs::read_to_string(latest.join(name)).ok().as_ref() == Some(contents)
+            })
+    }
+
+    /// Write a save taken at `stamp`. The files are written to a hidden
+    /// directory first and moved in to place once complete, so a save is never
+    /// seen half written. The hidden directory is named after the process too,
+    /// so autosaves running at once don't write in to each other's.
+    pub fn write(&self, stamp: u128, files: &[File]) -> Result<PathBuf, String> {
+        let save = self.dir.join(stamp.to_string());
+        let partial = self.dir.join(format!(".{}.{}", stamp, process::id()));
+
+        fs::create_dir_all(&partial).map_err(|e| {
+            format!("Could not create the directory {}. Error: {}", partial.display(), e)
+        })?;
+
+        for (name, contents) in files {
+            let file = partial.join(name);
+            fs::write(&file, contents)
+                .map_err(|e| format!("Could not write the file {}. Error: {}", file.display(), e))?;
+        }
+
+        fs::rename(&partial, &save).map_err(|e| {
+            let _ = fs::remove_dir_all(&partial);
+            format!("Could not move the save in to {}. Error: {}", save.display(), e)
+        })?;
+
+        Ok(save)
+    }
+
+    /// Remove all but the `keep` most recent saves.
+    pub fn prune(&self, keep: usize) -> Result<(), String> {
+        let saves = self.saves();
+        let remove = saves.len().saturating_sub(keep);
+
+        for save in &saves[..remove] {
+            fs::remove_dir_all(save)
+                .map_err(|e| format!("Could not remove the save {}. Error: {}", save.display(), e))?;
+        }
+
+        Ok(())
+    }
+}
+
+/// The names of the projects in a save, sorted.
+pub fn projects(save: &Path) -> Vec<String> {
+    let entries = match fs::read_dir(save) {
+        Ok(entries) => entries,
+        Err(_) => return vec![],
+    };
+
+    let mut names: Vec<String> = entries
+        .filter_map(|e| e.ok())
+        .map(|e| e.path())
+        .filter(|p| p.extension().and_then(|e| e.to_str()) == Some(CONFIG_EXTENSION))
+        .filter_map(|p| p.file_stem().and_then(|s| s.to_str()).map(|s| s.to_string()))
+        .collect();
+
+    names.sort();
+    names
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use common::rand_names;
+
+    fn files(contents: &str) -> Vec<File> {
+        vec![
+            ("api.yml".to_string(), contents.to_string()),
+            ("web.yml".to_string(), contents.to_string()),
+        ]
+    }
+
+    #[test]
+    fn expect_saves_to_be_sorted_by_time() {
+        let state = State::new(rand_names::project_path());
+        let _ = state.write(900, &files("a"));
+        let _ = state.write(1000, &files("b"));
+        let saves = state.saves();
+        let _ = fs::remove_dir_all(&state.dir);
+
+        assert_eq!(saves, vec![state.dir.join("900"), state.dir.join("1000")])
+    }
+
+    #[test]
+    fn expect_no_partial_directory_after_a_write() {
+        let state = State::new(rand_names::project_path());
+        let save = state.write(1, &files("a"));
+        let partial = state.dir.join(".1").exists();
+        let projects = projects(&state.dir.join("1"));
+        let _ = fs::remove_dir_all(&state.dir);
+
+        assert!(save.is_ok());
+        assert!(!partial);
+        assert_eq!(projects, vec!["api", "web"])
+    }
+
+    #[test]
+    fn expect_latest_to_match_the_same_files() {
+        let state = State::new(rand_names::project_path());
+        let _ = state.write(1, &files("a"));
+        let same = state.is_latest(&files("a"));
+        let changed = state.is_latest(&files("b"));
+        let fewer = state.is_latest(&files("a")[..1]);
+        let _ = fs::remove_dir_all(&state.dir);
+
+        assert!(same);
+        assert!(!changed);
+        assert!(!fewer)
+    }
+
+    #[test]
+    fn expect_prune_to_keep_the_newest_saves() {
+        let state = State::new(rand_names::project_path());
+        for stamp in 1..5 {
+            let _ = state.write(stamp, &files("a"));
+        }
+        let _ = state.prune(2);
+        let saves = state.saves();
+        let _ = fs::remove_dir_all(&state.dir);
+
+        assert_eq!(saves, vec![state.dir.join("3"), state.dir.join("4")])
+    }
+
+    #[test]
+    fn expect_no_latest_without_saves() {
+        let state = State::new(rand_names::project_path());
+        assert_eq!(state.latest(), None)
+    }
+}
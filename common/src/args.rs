@@ -4,28 +4,102 @@ use serde::Deserialize;
 
 /// The args struct for taking arguments passed in from the command line
 /// and making it easier to pass around.
+/// `flag_all` to snapshot or restore every session, or exec in every pane
+/// `flag_allow` the only commands a snapshot should restore
+/// `flag_confirm` to list the panes a command goes to and ask first
 /// `flag_d` is whether the session should be daemonzied
+/// `flag_deny` commands a snapshot should never restore
+/// `flag_tag` the tag of the panes to send a command to
 /// `flag_v` to print the version
 /// `flag_f` to force overwrite of a file
+/// `flag_history` the lines of each pane's history saved with its contents
+/// `flag_hooks` to set up tmux hooks running autosaves
+/// `flag_interval` the seconds between autosaves
+/// `flag_keep` the number of autosaves to keep
+/// `flag_once` to autosave once and exit
+/// `flag_output` the file to record a session to
 /// `flag_p` the project directory to read or write to
 /// `flag_t` the session to read from
 /// `flag_debug` run inline print statements for debugging
+/// `flag_nested` how to attach when muxed is run from inside of tmux
+/// `flag_socket_name` the named tmux socket to use
+/// `flag_socket_path` the path of the tmux socket to use
+/// `flag_tmux_command` the tmux program to call
+/// `flag_tmux_config` the tmux config file to start the server with
+/// `flag_wait` to wait for a command sent with run to finish
+/// `flag_window` the window or pane to send a command to
+/// `flag_with_contents` whether to save or replay pane contents
+/// `flag_worktree` the branch to open the project's git worktree for
+/// `arg_command` the command to run in a project's window
+/// `arg_dir` the directory to snapshot every session in to
+/// `arg_file` the file to import
+/// `arg_format` the tool a file is imported from
+/// `arg_pane` the pane to restart
 /// `arg_project` the project file to read
+/// `arg_target` the window or pane to run a command in
+/// `cmd_autosave`
 /// `cmd_edit`
+/// `cmd_exec`
+/// `cmd_import`
+/// `cmd_last`
+/// `cmd_list`
 /// `cmd_new` literally nothing
+/// `cmd_pick`
+/// `cmd_recent`
+/// `cmd_record`
+/// `cmd_restart`
+/// `cmd_restore`
+/// `cmd_run`
 /// `cmd_snapshot` not sure why I have these
 ///
-#[derive(Debug, Deserialize)]
+#[derive(Clone, Debug, Deserialize)]
 pub struct Args {
+    pub flag_all: bool,
+    pub flag_allow: Option<String>,
+    pub flag_confirm: bool,
     pub flag_debug: bool,
     pub flag_d: bool,
+    pub flag_deny: Option<String>,
     pub flag_f: bool,
+    pub flag_history: Option<usize>,
+    pub flag_hooks: bool,
+    pub flag_interval: Option<u64>,
+    pub flag_keep: Option<usize>,
+    pub flag_nested: Option<String>,
+    pub flag_once: bool,
+    pub flag_output: Option<String>,
     pub flag_p: Option<String>,
+    pub flag_socket_name: Option<String>,
+    pub flag_socket_path: Option<String>,
     pub flag_t: Option<String>,
+    pub flag_tag: Option<String>,
+    pub flag_tmux_command: Option<String>,
+    pub flag_tmux_config: Option<String>,
     pub flag_v: bool,
+    pub flag_wait: bool,
+    pub flag_window: Option<String>,
+    pub flag_with_contents: bool,
+    pub flag_worktree: Option<String>,
+    pub arg_command: Vec<String>,
+    pub arg_dir: String,
+    pub arg_file: String,
+    pub arg_format: String,
+    pub arg_pane: String,
     pub arg_project: String,
+    pub arg_target: String,
+    pub cmd_autosave: bool,
     pub cmd_edit: bool,
+    pub cmd_exec: bool,
+    pub cmd_import: bool,
+    pub cmd_last: bool,
+    pub cmd_list: bool,
     pub cmd_new: bool,
+    pub cmd_pick: bool,
+    pub cmd_recent: bool,
+    pub cmd_record: bool,
+    pub cmd_restart: bool,
+    pub cmd_restore: bool,
+    pub cmd_run: bool,
     pub cmd_snapshot: bool,
 }
 
@@ -34,16 +108,53 @@ impl Default for Args {
         let name = format!("{}", random::<u16>());
 
         Args {
+            arg_command: vec![],
+            arg_dir: String::new(),
+            arg_file: String::new(),
+            arg_format: String::new(),
+            arg_pane: String::new(),
             arg_project: name,
+            arg_target: String::new(),
+            cmd_autosave: false,
             cmd_edit: false,
+            cmd_exec: false,
+            cmd_import: false,
+            cmd_last: false,
+            cmd_list: false,
             cmd_new: true,
+            cmd_pick: false,
+            cmd_recent: false,
+            cmd_record: false,
+            cmd_restart: false,
+            cmd_restore: false,
+            cmd_run: false,
             cmd_snapshot: false,
+            flag_all: false,
+            flag_allow: None,
+            flag_confirm: false,
             flag_d: true,
             flag_debug: false,
+            flag_deny: None,
             flag_f: false,
+            flag_history: None,
+            flag_hooks: false,
+            flag_interval: None,
+            flag_keep: None,
+            flag_nested: None,
+            flag_once: false,
+            flag_output: None,
             flag_p: None,
+            flag_socket_name: None,
+            flag_socket_path: None,
             flag_t: None,
+            flag_tag: None,
+            flag_tmux_command: None,
+            flag_tmux_config: None,
             flag_v: false,
+            flag_wait: false,
+            flag_window: None,
+            flag_with_contents: false,
+            flag_worktree: None,
         }
     }
 }
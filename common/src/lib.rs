@@ -5,4 +5,7 @@ extern crate serde;
 pub mod args;
 pub mod first_run;
 pub mod project_paths;
+pub mod procfile;
 pub mod rand_names;
+pub mod shell;
+pub mod tmux;
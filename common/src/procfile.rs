@@ -0,0 +1,229 @@
+//! Procfiles and the `.env` files next to them, read the way foreman reads
+//! them.
+//!
+//! ```text
+//! web: bundle exec rails s -p $PORT
+//! worker: bundle exec sidekiq
+//! ```
+use shell::shell_quote;
+use std::fs;
+use std::io::ErrorKind;
+use std::path::Path;
+
+/// The file foreman loads variables from, next to the Procfile.
+pub static ENV_FILE: &str = ".env";
+
+/// An entry of a Procfile. `name: command`
+#[derive(Clone, Debug, PartialEq)]
+pub struct Process {
+    pub name: String,
+    pub command: String,
+}
+
+/// A variable from a `.env` file.
+pub type Variable = (String, String);
+
+/// Every process in a Procfile. Lines that aren't `name: command` are skipped,
+/// like comments and blank lines.
+pub fn parse(text: &str) -> Vec<Process> {
+    text.lines()
+        .filter_map(|line| {
+            let mut parts = line.splitn(2, ':');
+            let name = parts.next()?.trim();
+            let command = parts.next()?.trim();
+            let valid = |c: char| c.is_ascii_alphanumeric() || c == '_' || c == '-';
+
+            if name.is_empty() || !name.chars().all(valid) || command.is_empty() {
+                return None;
+            };
+
+            Some(Process {
+                name: name.to_string(),
+                command: command.to_string(),
+            })
+        })
+        .collect()
+}
+
+pub fn read(file: &Path) -> Result<Vec<Process>, String> {
+    let text = fs::read_to_string(file)
+        .map_err(|e| format!("Could not read the Procfile {}. Error: {}", file.display(), e))?;
+
+    Ok(parse(&text))
+}
+
+/// Every variable in a `.env` file. Values can be single quoted and taken as
+/// written, or double quoted with `\n` for new lines. Comments after a value
+/// are dropped.
+/// ex. `export DATABASE_URL="postgres://localhost/dev" # local only`
+pub fn parse_env(text: &str) -> Vec<Variable> {
+    text.lines()
+        .filter_map(|line| {
+            let line = line.trim().trim_start_matches("export ");
+            let mut parts = line.splitn(2, '=');
+            let name = parts.next()?.trim();
+            let value = parts.next()?.trim();
+            let valid = |c: char| c.is_ascii_alphanumeric() || c == '_';
+
+            if name.is_empty() || !name.chars().all(valid) {
+                return None;
+            };
+
+            Some((name.to_string(), unquote(value)))
+        })
+        .collect()
+}
+
+/// The variables of a `.env` file. A missing file has none.
+pub fn read_env(file: &Path) -> Result<Vec<Variable>, String> {
+    match fs::read_to_string(file) {
+        Ok(text) => Ok(parse_env(&text)),
+        Err(ref e) if e.kind() == ErrorKind::NotFound => Ok(vec![]),
+        Err(e) => Err(format!("Could not read the env file {}. Error: {}", file.display(), e)),
+    }
+}
+
+/// The command run with the variables exported before it.
+/// ex. `export PORT=3000; bundle exec rails s -p $PORT`
+pub fn with_env(command: &str, env: &[Variable]) -> String {
+    if env.is_empty() {
+        return command.to_string();
+    };
+
+    let exports: Vec<String> = env
+        .iter()
+        .map(|(name, value)| format!("{}={}", name, shell_quote(value)))
+        .collect();
+
+    format!("export {}; {}", exports.join(" "), command)
+}
+
+fn unquote(value: &str) -> String {
+    // The closing quote, passing over the escaped ones of double quoted values.
+    let closing = |q: char| {
+        let mut escaped = false;
+        value
+            .char_indices()
+            .skip(1)
+            .find(|&(_, c)| {
+                let found = c == q && !escaped;
+                escaped = q == '"' && c == '\\' && !escaped;
+                found
+            })
+            .map(|(i, _)| i)
+    };
+
+    if value.starts_with('\'') {
+        if let Some(end) = closing('\'') {
+            return value[1..end].to_string();
+        };
+    };
+
+    if value.starts_with('"') {
+        if let Some(end) = closing('"') {
+            return value[1..end]
+                .replace("\\n", "\n")
+                .replace("\\\"", "\"")
+                .replace("\\\\", "\\");
+        };
+    };
+
+    // An unquoted value ends where a comment starts.
+    match value.find(" #") {
+        Some(i) => value[..i].trim_end().to_string(),
+        None => value.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn process(name: &str, command: &str) -> Process {
+        Process {
+            name: name.to_string(),
+            command: command.to_string(),
+        }
+    }
+
+    #[test]
+    fn expect_processes_in_order() {
+        let text = "web: bundle exec rails s -p $PORT\n# jobs\n\nworker:   bundle exec sidekiq\n";
+        assert_eq!(
+            parse(text),
+            vec![
+                process("web", "bundle exec rails s -p $PORT"),
+                process("worker", "bundle exec sidekiq")
+            ]
+        )
+    }
+
+    #[test]
+    fn expect_colons_in_commands_to_stay() {
+        let text = "assets: webpack-dev-server --host 0.0.0.0:8080\n";
+        assert_eq!(parse(text), vec![process("assets", "webpack-dev-server --host 0.0.0.0:8080")])
+    }
+
+    #[test]
+    fn expect_lines_without_a_name_to_be_skipped() {
+        assert_eq!(parse("just a note\nweb server: rails s\n: ls\n"), vec![])
+    }
+
+    #[test]
+    fn expect_env_values_in_every_form() {
+        let text = "PORT=3000\nexport RACK_ENV=development\n# DEBUG=1\nGREETING=\"hi\\nthere\"\nRAW='$HOME'\n";
+        assert_eq!(
+            parse_env(text),
+            vec![
+                ("PORT".to_string(), "3000".to_string()),
+                ("RACK_ENV".to_string(), "development".to_string()),
+                ("GREETING".to_string(), "hi\nthere".to_string()),
+                ("RAW".to_string(), "$HOME".to_string()),
+            ]
+        )
+    }
+
+    #[test]
+    fn expect_comments_after_unquoted_values_to_be_dropped() {
+        let text = "PORT=3000 # web\nCOLOR=#fff\n";
+        assert_eq!(
+            parse_env(text),
+            vec![
+                ("PORT".to_string(), "3000".to_string()),
+                ("COLOR".to_string(), "#fff".to_string()),
+            ]
+        )
+    }
+
+    #[test]
+    fn expect_anything_after_the_closing_quote_to_be_dropped() {
+        let text = "GREETING=\"say \\\"hi\\\" # not a comment\" # a comment\nRAW='a # b' trailing\n";
+        assert_eq!(
+            parse_env(text),
+            vec![
+                ("GREETING".to_string(), "say \"hi\" # not a comment".to_string()),
+                ("RAW".to_string(), "a # b".to_string()),
+            ]
+        )
+    }
+
+    #[test]
+    fn expect_exports_before_the_command() {
+        let env = vec![
+            ("PORT".to_string(), "3000".to_string()),
+            ("NAME".to_string(), "my app".to_string()),
+        ];
+        assert_eq!(with_env("rails s -p $PORT", &env), "export PORT=3000 NAME='my app'; rails s -p $PORT")
+    }
+
+    #[test]
+    fn expect_command_alone_without_env() {
+        assert_eq!(with_env("rails s", &[]), "rails s")
+    }
+
+    #[test]
+    fn expect_no_variables_without_an_env_file() {
+        let file = Path::new("/tmp/muxed-no-such-dir/.env");
+        assert_eq!(read_env(file), Ok(vec![]))
+    }
+}
@@ -32,6 +32,12 @@ impl ProjectPaths {
             project_file,
         }
     }
+
+    /// The default project directory, where muxed keeps its own files even
+    /// when projects are read from elsewhere with -p.
+    pub fn config_directory(&self) -> PathBuf {
+        self.home_directory.join(MUXED_FOLDER)
+    }
 }
 
 /// A common method for returning the project directory and filepath. The method
@@ -75,6 +81,26 @@ pub fn project_paths(args: &Args) -> ProjectPaths {
     ProjectPaths::new(homedir, project_directory, project_fullpath)
 }
 
+/// Expand a leading `~/` to the home directory, like a shell would. Settings
+/// read from project files never go through a shell.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use common::project_paths::expand_home;
+/// use std::path::PathBuf;
+///
+/// assert_eq!(expand_home("~/.tmux.conf"), PathBuf::from("/home/me/.tmux.conf"));
+/// assert_eq!(expand_home("/etc/tmux.conf"), PathBuf::from("/etc/tmux.conf"))
+/// ```
+pub fn expand_home(path: &str) -> PathBuf {
+    if path.starts_with("~/") {
+        homedir().expect("Home dir could not be expanded").join(&path[2..])
+    } else {
+        PathBuf::from(path)
+    }
+}
+
 /// A Thin wrapper around the home_dir crate. This is so we can swap the default
 /// dir out during testing.
 #[cfg(not(any(test, doctest)))]
@@ -92,6 +118,22 @@ fn homedir() -> Option<PathBuf> {
 mod test {
     use super::*;
 
+    #[test]
+    fn expects_tilde_slash_to_expand_to_homedir() {
+        assert_eq!(expand_home("~/.tmux.conf"), PathBuf::from("/tmp/.tmux.conf"));
+        assert_eq!(expand_home("tmux~/.conf"), PathBuf::from("tmux~/.conf"))
+    }
+
+    #[test]
+    fn expects_config_dir_to_ignore_project_dir() {
+        let args = Args {
+            flag_p: Some("/tmp/projects".to_string()),
+            ..Default::default()
+        };
+
+        assert_eq!(project_paths(&args).config_directory(), PathBuf::from("/tmp/.muxed"))
+    }
+
     #[test]
     fn expects_tmp_as_default_homedir() {
         let args: Args = Default::default();
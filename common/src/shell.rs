@@ -0,0 +1,51 @@
+//! Writing commands for a shell to run.
+
+/// Quote an argument for a POSIX shell. Arguments made of only safe
+/// characters are left bare, anything else is single quoted.
+pub fn shell_quote(arg: &str) -> String {
+    let safe = |c: char| c.is_ascii_alphanumeric() || "-_./=:,+@%^".contains(c);
+
+    if !arg.is_empty() && arg.chars().all(safe) {
+        return arg.to_string();
+    };
+
+    format!("'{}'", arg.replace('\'', "'\\''"))
+}
+
+/// Wrap a shell command in `run-shell` for tmux, in the background so the
+/// hook running it doesn't hold up tmux. The command is double quoted for
+/// tmux.
+pub fn run_shell(command: &str) -> String {
+    let escaped = command
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('$', "\\$");
+
+    format!("run-shell -b \"{}\"", escaped)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn expect_bare_args_to_stay_bare() {
+        assert_eq!(shell_quote("log/development.log"), "log/development.log")
+    }
+
+    #[test]
+    fn expect_single_quotes_to_be_escaped() {
+        assert_eq!(shell_quote("it's done"), "'it'\\''s done'")
+    }
+
+    #[test]
+    fn expect_empty_arg_to_be_quoted() {
+        assert_eq!(shell_quote(""), "''")
+    }
+
+    #[test]
+    fn expect_run_shell_to_escape_for_tmux() {
+        let command = run_shell("muxed autosave --once -p '$HOME/\"x\"'");
+        assert_eq!(command, "run-shell -b \"muxed autosave --once -p '\\$HOME/\\\"x\\\"'\"")
+    }
+}
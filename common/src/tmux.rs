@@ -0,0 +1,152 @@
+//! The tmux server muxed talks to. By default that's whatever `tmux` is found
+//! in the `$PATH` running on the default socket. These settings allow pointing
+//! muxed at a different tmux build or an isolated server.
+use args::Args;
+use project_paths::expand_home;
+use std::process::Command;
+
+/// The program to call commands on when nothing else was chosen.
+static TMUX_NAME: &str = "tmux";
+
+/// A description of how to reach a tmux server.
+/// `command`: The tmux program to run, ex. `wemux`. May contain extra args.
+/// `socket_name`: A named socket, passed to tmux's `-L`.
+/// `socket_path`: A full socket path, passed to tmux's `-S`.
+/// `config_file`: An alternative tmux.conf, passed to tmux's `-f`.
+///
+/// Paths starting with `~/` are expanded, tmux doesn't do it itself.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Server {
+    pub command: Option<String>,
+    pub socket_name: Option<String>,
+    pub socket_path: Option<String>,
+    pub config_file: Option<String>,
+}
+
+impl Server {
+    pub fn new(
+        command: Option<String>,
+        socket_name: Option<String>,
+        socket_path: Option<String>,
+        config_file: Option<String>,
+    ) -> Server {
+        let expand = |path: String| expand_home(&path).to_string_lossy().to_string();
+
+        Server {
+            command,
+            socket_name,
+            socket_path: socket_path.map(expand),
+            config_file: config_file.map(expand),
+        }
+    }
+
+    /// Use the settings chosen here, and fall back to the settings of `other`
+    /// for anything left empty. Used to let the cli override the project file.
+    pub fn or(self, other: Server) -> Server {
+        Server {
+            command: self.command.or(other.command),
+            socket_name: self.socket_name.or(other.socket_name),
+            socket_path: self.socket_path.or(other.socket_path),
+            config_file: self.config_file.or(other.config_file),
+        }
+    }
+
+    /// The program and the leading args that select the server. Every call to
+    /// tmux starts with these.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use common::tmux::Server;
+    ///
+    /// let server = Server::new(None, Some("work".to_string()), None, None);
+    /// assert_eq!(server.args(), vec!["tmux", "-L", "work"]);
+    /// ```
+    pub fn args(&self) -> Vec<&str> {
+        let mut args: Vec<&str> = match self.command.as_ref() {
+            Some(c) if !c.trim().is_empty() => c.split_whitespace().collect(),
+            _ => vec![TMUX_NAME],
+        };
+
+        if let Some(name) = self.socket_name.as_ref() {
+            args.extend_from_slice(&["-L", name]);
+        };
+
+        if let Some(path) = self.socket_path.as_ref() {
+            args.extend_from_slice(&["-S", path]);
+        };
+
+        if let Some(file) = self.config_file.as_ref() {
+            args.extend_from_slice(&["-f", file]);
+        };
+
+        args
+    }
+
+    /// A `Command` ready to have the tmux command args appended.
+    pub fn command(&self) -> Command {
+        let args = self.args();
+        let (program, args) = args.split_first().expect("A tmux program is always set");
+
+        let mut command = Command::new(program);
+        command.args(args);
+        command
+    }
+}
+
+impl<'a> From<&'a Args> for Server {
+    fn from(args: &'a Args) -> Self {
+        Server::new(
+            args.flag_tmux_command.clone(),
+            args.flag_socket_name.clone(),
+            args.flag_socket_path.clone(),
+            args.flag_tmux_config.clone(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn expect_tmux_by_default() {
+        let server: Server = Default::default();
+        assert_eq!(server.args(), vec!["tmux"])
+    }
+
+    #[test]
+    fn expect_custom_command_with_args() {
+        let server = Server::new(Some("tmux -2".to_string()), None, None, None);
+        assert_eq!(server.args(), vec!["tmux", "-2"])
+    }
+
+    #[test]
+    fn expect_socket_path_and_config() {
+        let server = Server::new(
+            Some("wemux".to_string()),
+            None,
+            Some("/tmp/client".to_string()),
+            Some("~/.tmux.client.conf".to_string()),
+        );
+        assert_eq!(
+            server.args(),
+            vec!["wemux", "-S", "/tmp/client", "-f", "/tmp/.tmux.client.conf"]
+        )
+    }
+
+    #[test]
+    fn expect_or_to_prefer_self() {
+        let cli = Server::new(None, Some("cli".to_string()), None, None);
+        let file = Server::new(
+            Some("wemux".to_string()),
+            Some("file".to_string()),
+            None,
+            None,
+        );
+        let server = cli.or(file);
+
+        assert_eq!(server.command, Some("wemux".to_string()));
+        assert_eq!(server.socket_name, Some("cli".to_string()))
+    }
+}
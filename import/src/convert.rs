@@ -0,0 +1,97 @@
+//! Reading the parts other tools' project files have in common. Importers
+//! fill in a `snapshot::project::Project`, the project file snapshots write.
+use snapshot::project::WindowEntry;
+use yaml_rust::Yaml;
+
+/// What couldn't be carried over. Imports go ahead regardless and the notes
+/// are printed once the project file is written.
+#[derive(Debug, Default, PartialEq)]
+pub struct Report {
+    pub notes: Vec<String>,
+}
+
+impl Report {
+    pub fn unsupported(&mut self, key: &str, reason: &str) {
+        self.notes.push(format!("`{}` {}", key, reason));
+    }
+}
+
+/// Commands from a string or a list of strings. Numbers are read as commands
+/// too, YAML doesn't quote them.
+pub fn command_list(yaml: &Yaml) -> Vec<String> {
+    match *yaml {
+        Yaml::Array(ref a) => a.iter().filter_map(scalar).collect(),
+        _ => scalar(yaml).into_iter().collect(),
+    }
+}
+
+/// Several commands run one after another in a single pane.
+pub fn join_commands(commands: &[String]) -> Option<String> {
+    if commands.is_empty() {
+        return None;
+    };
+
+    Some(commands.join("; "))
+}
+
+/// Commands every pane of a window runs before its own. A window without
+/// panes runs them as its command.
+pub fn run_before(window: &mut WindowEntry, before: &[String]) {
+    if before.is_empty() {
+        return;
+    };
+
+    if window.panes.is_empty() {
+        let mut commands = before.to_vec();
+        commands.extend(window.command.take());
+        window.command = join_commands(&commands);
+    };
+
+    for pane in &mut window.panes {
+        let mut commands = before.to_vec();
+        commands.extend(pane.command.take());
+        pane.command = join_commands(&commands);
+    }
+}
+
+/// A scalar as a string, whatever type YAML read it as.
+pub fn scalar(yaml: &Yaml) -> Option<String> {
+    match *yaml {
+        Yaml::String(ref s) => Some(s.clone()),
+        Yaml::Integer(i) => Some(i.to_string()),
+        Yaml::Real(ref r) => Some(r.clone()),
+        Yaml::Boolean(b) => Some(b.to_string()),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use snapshot::project::PaneEntry;
+    use yaml_rust::YamlLoader;
+
+    #[test]
+    fn expect_numbers_to_be_read_as_commands() {
+        let yaml = YamlLoader::load_from_str("- ls\n- 42").unwrap();
+        assert_eq!(command_list(&yaml[0]), vec!["ls", "42"])
+    }
+
+    #[test]
+    fn expect_commands_before_to_run_first_in_every_pane() {
+        let mut window = WindowEntry {
+            panes: vec![
+                PaneEntry {
+                    command: Some("vim".to_string()),
+                    ..Default::default()
+                },
+                PaneEntry::default(),
+            ],
+            ..Default::default()
+        };
+        run_before(&mut window, &["source .env".to_string()]);
+
+        assert_eq!(window.panes[0].command, Some("source .env; vim".to_string()));
+        assert_eq!(window.panes[1].command, Some("source .env".to_string()))
+    }
+}
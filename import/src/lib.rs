@@ -0,0 +1,140 @@
+//! Muxedimport. Converts project files from other tmux project managers in to
+//! muxed projects.
+extern crate common;
+extern crate new;
+extern crate snapshot;
+extern crate yaml_rust;
+
+pub mod convert;
+pub mod procfile;
+pub mod teamocil;
+pub mod tmuxinator;
+pub mod tmuxp;
+
+use common::args::Args;
+use common::first_run::check_first_run;
+use common::project_paths::project_paths;
+use new::write_template;
+use convert::Report;
+use snapshot::project::Project;
+use std::fs;
+use std::path::PathBuf;
+use yaml_rust::YamlLoader;
+
+/// The main execution method.
+/// Reads a project file written for another tool and writes it out as a muxed
+/// project, named after the file. Anything that couldn't be carried over is
+/// listed once the project is written.
+///
+/// # Examples
+///
+/// You can run the command:
+///
+/// ```
+/// $ ./muxed import tmuxinator ~/.config/tmuxinator/my_project.yml
+/// ```
+///
+/// or
+///
+/// ```
+/// $ ./muxed import tmuxp ~/.tmuxp/my_project.json
+/// ```
+///
+/// or
+///
+/// ```
+/// $ ./muxed import procfile ~/projects/my_project/Procfile
+/// ```
+pub fn exec(args: Args) -> Result<(), String> {
+    let file = PathBuf::from(&args.arg_file);
+    let text = fs::read_to_string(&file)
+        .map_err(|e| format!("Could not read the file {}. Error: {}", file.display(), e))?;
+
+    let mut report = Report::default();
+
+    // Procfiles are all called Procfile, they're named after their directory.
+    let (project, named_after) = if args.arg_format == "procfile" {
+        let project = procfile::convert(&file, &text, &mut report)?;
+        let dir = PathBuf::from(project.root.clone().unwrap_or_default());
+        (project, dir)
+    } else {
+        (convert(&args.arg_format, &text, &mut report)?, file.clone())
+    };
+
+    let name = named_after
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .ok_or_else(|| format!("Could not name a project after {}.", file.display()))?;
+
+    let args = Args {
+        arg_project: name.to_string(),
+        ..args
+    };
+    let project_paths = project_paths(&args);
+    check_first_run(&project_paths.project_directory)?;
+
+    write_template(project.to_yaml()?, &project_paths.project_file, args.flag_f)?;
+
+    println!(
+        "\u{270C} {} has been imported to {}",
+        file.display(),
+        project_paths.project_file.display()
+    );
+
+    if !report.notes.is_empty() {
+        println!("\nSome of it couldn't be carried over:");
+        for note in &report.notes {
+            println!("  - {}", note);
+        }
+    };
+
+    Ok(())
+}
+
+/// Read a project in the given format. JSON is read as YAML, which it is.
+pub fn convert(format: &str, text: &str, report: &mut Report) -> Result<Project, String> {
+    let yaml = YamlLoader::load_from_str(text).map_err(|e| format!("The file isn't valid YAML: {}", e))?;
+    let doc = yaml.first().ok_or_else(|| "The file is empty.".to_string())?;
+
+    match format {
+        "teamocil" => teamocil::convert(doc, report),
+        "tmuxinator" => tmuxinator::convert(text, doc, report),
+        "tmuxp" => tmuxp::convert(doc, report),
+        _ => Err(format!(
+            "Muxed can't import {} projects. Try tmuxinator, tmuxp, teamocil or procfile.",
+            format
+        )),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use common::rand_names;
+
+    #[test]
+    fn expect_unknown_formats_to_err() {
+        let result = convert("screen", "windows:\n  - a: ls\n", &mut Report::default());
+        assert!(result.is_err())
+    }
+
+    #[test]
+    fn expect_project_named_after_the_file() {
+        let dir = rand_names::project_path();
+        let _ = fs::create_dir_all(&dir);
+        let file = dir.join("sample.yml");
+        let _ = fs::write(&file, "windows:\n  - editor: vim\n");
+
+        let result = exec(Args {
+            arg_format: "tmuxinator".to_string(),
+            arg_file: file.display().to_string(),
+            flag_p: Some(dir.join("muxed").display().to_string()),
+            ..Default::default()
+        });
+        let written = fs::read_to_string(dir.join("muxed").join("sample.yml"));
+        let _ = fs::remove_dir_all(&dir);
+
+        assert_eq!(result, Ok(()));
+        assert_eq!(written.unwrap(), "---\nwindows:\n  - editor: vim\n")
+    }
+}
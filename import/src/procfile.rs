@@ -0,0 +1,74 @@
+//! Procfiles, a window for every process. The project's root is the
+//! Procfile's directory, where foreman runs them.
+//! ex. `web: bundle exec rails s -p $PORT`
+use common::procfile::{self, ENV_FILE};
+use convert::Report;
+use snapshot::project::{Project, WindowEntry};
+use std::fs;
+use std::path::Path;
+
+pub fn convert(file: &Path, text: &str, report: &mut Report) -> Result<Project, String> {
+    let processes = procfile::parse(text);
+
+    if processes.is_empty() {
+        return Err("The Procfile has no processes to import.".into());
+    };
+
+    let file = fs::canonicalize(file)
+        .map_err(|e| format!("Could not find the Procfile {}. Error: {}", file.display(), e))?;
+    let dir = file.parent().unwrap_or_else(|| Path::new("/"));
+
+    // The variables are read when the project is opened, not copied in to it.
+    if dir.join(ENV_FILE).exists() {
+        report.unsupported(
+            ENV_FILE,
+            "wasn't copied in to the project. Use `procfile: Procfile` in the project to load it.",
+        );
+    };
+
+    Ok(Project {
+        root: Some(dir.display().to_string()),
+        windows: processes
+            .into_iter()
+            .map(|p| WindowEntry {
+                name: p.name,
+                command: Some(p.command),
+                ..Default::default()
+            })
+            .collect(),
+        ..Default::default()
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use common::rand_names;
+
+    #[test]
+    fn expect_a_window_per_process_in_the_procfile_directory() {
+        let dir = rand_names::project_path();
+        let _ = fs::create_dir_all(&dir);
+        let file = dir.join("Procfile");
+        let text = "web: rails s\nworker: sidekiq\n";
+        let _ = fs::write(&file, text);
+        let _ = fs::write(dir.join(".env"), "PORT=3000\n");
+
+        let mut report = Report::default();
+        let project = convert(&file, text, &mut report);
+        let root = fs::canonicalize(&dir).unwrap();
+        let _ = fs::remove_dir_all(&dir);
+        let project = project.unwrap();
+
+        assert_eq!(project.root, Some(root.display().to_string()));
+        assert_eq!(project.windows[0].name, "web");
+        assert_eq!(project.windows[1].command, Some("sidekiq".to_string()));
+        assert_eq!(report.notes.len(), 1)
+    }
+
+    #[test]
+    fn expect_err_without_processes() {
+        let result = convert(Path::new("Procfile"), "# nothing yet\n", &mut Report::default());
+        assert!(result.is_err())
+    }
+}
@@ -0,0 +1,182 @@
+//! teamocil layouts. ex. `~/.teamocil/my_project.yml`
+//!
+//! ```yaml
+//! name: my_project
+//! windows:
+//!   - name: editor
+//!     root: ~/projects/my_project
+//!     layout: main-vertical
+//!     focus: true
+//!     panes:
+//!       - vim
+//!       - commands:
+//!           - bundle
+//!           - guard
+//!         focus: true
+//! ```
+use convert::{command_list, join_commands, scalar, Report};
+use snapshot::project::{PaneEntry, Project, WindowEntry};
+use yaml_rust::Yaml;
+
+/// Keys muxed has a different way of doing, and what to do instead.
+static ALTERNATIVES: &[(&str, &str)] = &[
+    ("session", "is from teamocil before 1.0. Upgrade the layout and import it again."),
+];
+
+pub fn convert(doc: &Yaml, report: &mut Report) -> Result<Project, String> {
+    let settings = doc
+        .as_hash()
+        .ok_or_else(|| "This doesn't look like a teamocil layout.".to_string())?;
+
+    let mut project = Project::default();
+
+    for (key, value) in settings {
+        let key = scalar(key).unwrap_or_default();
+
+        match key.as_ref() {
+            "name" => project.name = scalar(value),
+            "windows" => {
+                for (i, window) in value.as_vec().into_iter().flatten().enumerate() {
+                    if let Some(window) = convert_window(window, i, report) {
+                        project.windows.push(window);
+                    };
+                }
+            }
+            _ => match ALTERNATIVES.iter().find(|(k, _)| *k == key) {
+                Some((_, reason)) => report.unsupported(&key, reason),
+                None => report.unsupported(&key, "isn't supported by muxed and was left out."),
+            },
+        };
+    }
+
+    if project.windows.is_empty() {
+        return Err("The teamocil layout has no windows to import.".into());
+    };
+
+    Ok(project)
+}
+
+fn convert_window(window: &Yaml, position: usize, report: &mut Report) -> Option<WindowEntry> {
+    let settings = window.as_hash()?;
+    let name = match scalar(&window["name"]) {
+        Some(name) => name,
+        None => {
+            report.unsupported(
+                &format!("windows.{}", position),
+                "has no `name`, muxed windows need one. It was left out.",
+            );
+            return None;
+        }
+    };
+
+    let mut converted = WindowEntry {
+        name: name.clone(),
+        ..Default::default()
+    };
+
+    for (key, value) in settings {
+        let key = scalar(key).unwrap_or_default();
+
+        match key.as_ref() {
+            "name" => (),
+            "root" => converted.path = scalar(value),
+            "layout" => converted.layout = scalar(value),
+            "focus" => converted.active = value.as_bool() == Some(true),
+            "panes" => {
+                for (i, pane) in value.as_vec().into_iter().flatten().enumerate() {
+                    converted.panes.push(convert_pane(pane, &name, i, report));
+                }
+            }
+            _ => report.unsupported(
+                &format!("windows.{}.{}", name, key),
+                "isn't supported by muxed and was left out.",
+            ),
+        };
+    }
+
+    Some(converted)
+}
+
+/// A pane is a command, or its settings with the commands in `commands`.
+fn convert_pane(pane: &Yaml, window: &str, position: usize, report: &mut Report) -> PaneEntry {
+    let settings = match pane.as_hash() {
+        Some(settings) => settings,
+        None => {
+            return PaneEntry {
+                command: join_commands(&command_list(pane)),
+                ..Default::default()
+            }
+        }
+    };
+
+    let mut converted = PaneEntry::default();
+
+    for (key, value) in settings {
+        let key = scalar(key).unwrap_or_default();
+
+        match key.as_ref() {
+            "commands" => converted.command = join_commands(&command_list(value)),
+            "focus" => converted.active = value.as_bool() == Some(true),
+            _ => report.unsupported(
+                &format!("windows.{}.panes.{}.{}", window, position, key),
+                "isn't supported by muxed and was left out.",
+            ),
+        };
+    }
+
+    converted
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use yaml_rust::YamlLoader;
+
+    fn import(text: &str) -> (Result<Project, String>, Report) {
+        let doc = YamlLoader::load_from_str(text).unwrap();
+        let mut report = Report::default();
+        let project = convert(&doc[0], &mut report);
+        (project, report)
+    }
+
+    #[test]
+    fn expect_pane_commands_to_be_joined() {
+        let text = "windows:\n  - name: editor\n    panes:\n      - commands: [bundle, guard]\n";
+        let (project, _) = import(text);
+        let pane = &project.unwrap().windows[0].panes[0];
+
+        assert_eq!(pane.command, Some("bundle; guard".to_string()))
+    }
+
+    #[test]
+    fn expect_old_format_to_be_reported() {
+        let text = "session:\n  name: old\nwindows:\n  - name: editor\n";
+        let (_, report) = import(text);
+
+        assert_eq!(report.notes.len(), 1);
+        assert!(report.notes[0].contains("before 1.0"))
+    }
+
+    #[test]
+    fn expect_unsupported_keys_to_be_reported() {
+        let text = "
+windows:
+  - name: editor
+    clear: true
+    panes:
+      - commands: [vim]
+        options: {}
+";
+        let (_, report) = import(text);
+
+        assert_eq!(report.notes.len(), 2);
+        assert!(report.notes[0].starts_with("`windows.editor.clear`"));
+        assert!(report.notes[1].starts_with("`windows.editor.panes.0.options`"))
+    }
+
+    #[test]
+    fn expect_err_without_windows() {
+        let (project, _) = import("name: empty\n");
+        assert!(project.is_err())
+    }
+}
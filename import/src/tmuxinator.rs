@@ -0,0 +1,367 @@
+//! tmuxinator project files. ex. `~/.config/tmuxinator/my_project.yml`
+//!
+//! ```yaml
+//! name: my_project
+//! root: ~/projects/my_project
+//! on_project_start: docker-compose up -d
+//! pre_window: nvm use
+//! startup_window: editor
+//! windows:
+//!   - editor:
+//!       layout: main-vertical
+//!       panes:
+//!         - vim
+//!         - guard
+//!   - server: bundle exec rails s
+//! ```
+use convert::{command_list, join_commands, run_before, scalar, Report};
+use snapshot::project::{PaneEntry, Project, WindowEntry};
+use yaml_rust::Yaml;
+
+/// Keys muxed has a different way of doing, and what to do instead.
+static ALTERNATIVES: &[(&str, &str)] = &[
+    ("attach", "isn't a project setting in muxed. Open the project with `muxed -d` instead."),
+];
+
+pub fn convert(text: &str, doc: &Yaml, report: &mut Report) -> Result<Project, String> {
+    let settings = doc
+        .as_hash()
+        .ok_or_else(|| "This doesn't look like a tmuxinator project.".to_string())?;
+
+    flag_erb(text, report);
+
+    let mut project = Project::default();
+    let mut startup_window = None;
+    let mut startup_pane = None;
+
+    for (key, value) in settings {
+        let key = scalar(key).unwrap_or_default();
+
+        match key.as_ref() {
+            "name" => project.name = scalar(value),
+            "root" | "project_root" => project.root = scalar(value),
+            "on_project_start" | "on_project_first_start" | "pre" => {
+                project.pre.append(&mut command_list(value))
+            }
+            "pre_window" | "pre_tab" => project.pre_window.append(&mut command_list(value)),
+            "socket_name" => project.socket_name = scalar(value),
+            "tmux_command" => project.tmux_command = scalar(value),
+            "tmux_options" | "cli_args" => {
+                project.tmux_config = tmux_config(value, &key, report);
+            }
+            "startup_window" => startup_window = scalar(value),
+            "startup_pane" => startup_pane = value.as_i64(),
+            "windows" | "tabs" => {
+                for window in value.as_vec().into_iter().flatten() {
+                    if let Some(window) = convert_window(window, report) {
+                        project.windows.push(window);
+                    };
+                }
+            }
+            _ => match ALTERNATIVES.iter().find(|(k, _)| *k == key) {
+                Some((_, reason)) => report.unsupported(&key, reason),
+                None => report.unsupported(&key, "isn't supported by muxed and was left out."),
+            },
+        };
+    }
+
+    if project.windows.is_empty() {
+        return Err("The tmuxinator project has no windows to import.".into());
+    };
+
+    if let Some(startup) = startup_window {
+        focus(&mut project, &startup, startup_pane, report);
+    } else if startup_pane.is_some() {
+        report.unsupported("startup_pane", "needs a `startup_window` to go with it.");
+    };
+
+    Ok(project)
+}
+
+/// A window is a single key hash. The value is a command, a list of commands
+/// or the window's settings.
+fn convert_window(window: &Yaml, report: &mut Report) -> Option<WindowEntry> {
+    let (name, value) = window.as_hash()?.iter().next()?;
+    let name = scalar(name)?;
+
+    let settings = match value.as_hash() {
+        Some(settings) => settings,
+        None => {
+            return Some(WindowEntry {
+                name,
+                command: join_commands(&command_list(value)),
+                ..Default::default()
+            })
+        }
+    };
+
+    let mut converted = WindowEntry {
+        name: name.clone(),
+        ..Default::default()
+    };
+    let mut pre = vec![];
+
+    for (key, value) in settings {
+        let key = scalar(key).unwrap_or_default();
+
+        match key.as_ref() {
+            "root" => converted.path = scalar(value),
+            "layout" => converted.layout = scalar(value),
+            "pre" => pre = command_list(value),
+            "panes" => {
+                for pane in value.as_vec().into_iter().flatten() {
+                    converted.panes.push(convert_pane(pane, &name, report));
+                }
+            }
+            _ => report.unsupported(
+                &format!("windows.{}.{}", name, key),
+                "isn't supported by muxed and was left out.",
+            ),
+        };
+    }
+
+    // A window's `pre` runs in each of its panes, before the pane's commands.
+    run_before(&mut converted, &pre);
+    Some(converted)
+}
+
+/// A pane is a command, a list of commands, or a named list of commands.
+fn convert_pane(pane: &Yaml, window: &str, report: &mut Report) -> PaneEntry {
+    let commands = match pane.as_hash().and_then(|h| h.iter().next()) {
+        Some((title, commands)) => {
+            let title = scalar(title).unwrap_or_default();
+            report.unsupported(
+                &format!("windows.{}.panes.{}", window, title),
+                "is a pane title, muxed doesn't name panes. Its commands were kept.",
+            );
+            command_list(commands)
+        }
+        None => command_list(pane),
+    };
+
+    PaneEntry {
+        command: join_commands(&commands),
+        ..Default::default()
+    }
+}
+
+/// Mark the startup window and pane active. Either is found by name or by
+/// number, counting from 0.
+fn focus(project: &mut Project, startup: &str, pane: Option<i64>, report: &mut Report) {
+    let position = project
+        .windows
+        .iter()
+        .position(|w| w.name == startup)
+        .or_else(|| startup.parse::<usize>().ok().filter(|i| *i < project.windows.len()));
+
+    let window = match position {
+        Some(i) => &mut project.windows[i],
+        None => {
+            report.unsupported("startup_window", "doesn't match any window and was left out.");
+            return;
+        }
+    };
+
+    window.active = true;
+
+    if let Some(i) = pane {
+        match window.panes.get_mut(i as usize) {
+            Some(pane) => pane.active = true,
+            None => report.unsupported("startup_pane", "doesn't match any pane and was left out."),
+        };
+    };
+}
+
+/// muxed can start the server with a config file, the only tmux option it
+/// takes. ex. `tmux_options: -f ~/.tmux.mac.conf`
+fn tmux_config(value: &Yaml, key: &str, report: &mut Report) -> Option<String> {
+    let options = scalar(value).unwrap_or_default();
+    let words: Vec<&str> = options.split_whitespace().collect();
+
+    match words.as_slice() {
+        ["-f", file] => Some(file.to_string()),
+        _ => {
+            report.unsupported(key, "only carries over as `-f <file>`, and was left out.");
+            None
+        }
+    }
+}
+
+/// tmuxinator runs project files through ERB before reading them. muxed
+/// doesn't, so templated lines are imported as written and need a look.
+fn flag_erb(text: &str, report: &mut Report) {
+    for (i, line) in text.lines().enumerate() {
+        if line.contains("<%") {
+            report.notes.push(format!(
+                "Line {} uses ERB, which muxed doesn't run. It was imported as written: {}",
+                i + 1,
+                line.trim()
+            ));
+        };
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use yaml_rust::YamlLoader;
+
+    fn import(text: &str) -> (Result<Project, String>, Report) {
+        let doc = YamlLoader::load_from_str(text).unwrap();
+        let mut report = Report::default();
+        let project = convert(text, &doc[0], &mut report);
+        (project, report)
+    }
+
+    #[test]
+    fn expect_project_settings_to_carry_over() {
+        let text = "
+name: sample
+root: ~/projects/sample
+socket_name: foo
+tmux_command: byobu
+tmux_options: -f ~/.tmux.mac.conf
+on_project_start: docker-compose up -d
+pre_window:
+  - nvm use
+  - export PORT=3000
+windows:
+  - server: rails s
+";
+        let (project, report) = import(text);
+        let project = project.unwrap();
+
+        assert_eq!(project.name, Some("sample".to_string()));
+        assert_eq!(project.root, Some("~/projects/sample".to_string()));
+        assert_eq!(project.socket_name, Some("foo".to_string()));
+        assert_eq!(project.tmux_command, Some("byobu".to_string()));
+        assert_eq!(project.tmux_config, Some("~/.tmux.mac.conf".to_string()));
+        assert_eq!(project.pre, vec!["docker-compose up -d"]);
+        assert_eq!(project.pre_window, vec!["nvm use", "export PORT=3000"]);
+        assert!(report.notes.is_empty())
+    }
+
+    #[test]
+    fn expect_every_window_form() {
+        let text = "
+windows:
+  - editor:
+      layout: main-vertical
+      root: app
+      panes:
+        - vim
+        -
+        - - bundle
+          - guard
+  - logs: tail -f log/development.log
+  - console:
+    - cd db
+    - psql
+  - shell:
+";
+        let (project, _) = import(text);
+        let windows = project.unwrap().windows;
+
+        assert_eq!(windows[0].layout, Some("main-vertical".to_string()));
+        assert_eq!(windows[0].path, Some("app".to_string()));
+        assert_eq!(
+            windows[0].panes.iter().map(|p| p.command.clone()).collect::<Vec<_>>(),
+            vec![Some("vim".to_string()), None, Some("bundle; guard".to_string())]
+        );
+        assert_eq!(windows[1].command, Some("tail -f log/development.log".to_string()));
+        assert_eq!(windows[2].command, Some("cd db; psql".to_string()));
+        assert_eq!(windows[3].command, None)
+    }
+
+    #[test]
+    fn expect_window_pre_to_run_in_every_pane() {
+        let text = "
+windows:
+  - editor:
+      pre: source .env
+      panes:
+        - vim
+        -
+";
+        let (project, _) = import(text);
+        let panes = &project.unwrap().windows[0].panes;
+
+        assert_eq!(panes[0].command, Some("source .env; vim".to_string()));
+        assert_eq!(panes[1].command, Some("source .env".to_string()))
+    }
+
+    #[test]
+    fn expect_startup_window_and_pane_to_be_active() {
+        let text = "
+startup_window: editor
+startup_pane: 1
+windows:
+  - logs: tail -f log/development.log
+  - editor:
+      panes:
+        - vim
+        - guard
+";
+        let (project, _) = import(text);
+        let windows = project.unwrap().windows;
+
+        assert!(!windows[0].active);
+        assert!(windows[1].active);
+        assert!(!windows[1].panes[0].active);
+        assert!(windows[1].panes[1].active)
+    }
+
+    #[test]
+    fn expect_startup_window_by_number() {
+        let (project, _) = import("startup_window: 1\nwindows:\n  - a: ls\n  - b: ls\n");
+        assert!(project.unwrap().windows[1].active)
+    }
+
+    #[test]
+    fn expect_unsupported_keys_to_be_reported() {
+        let text = "
+attach: false
+on_project_exit: docker-compose down
+tmux_options: -2
+windows:
+  - editor:
+      synchronize: after
+      panes:
+        - vim
+";
+        let (_, report) = import(text);
+
+        assert_eq!(report.notes.len(), 4);
+        assert!(report.notes[0].starts_with("`attach`"));
+        assert!(report.notes[1].starts_with("`on_project_exit`"));
+        assert!(report.notes[2].starts_with("`tmux_options`"));
+        assert!(report.notes[3].starts_with("`windows.editor.synchronize`"))
+    }
+
+    #[test]
+    fn expect_pane_titles_to_be_reported_and_commands_kept() {
+        let text = "windows:\n  - editor:\n      panes:\n        - server:\n          - rails s\n";
+        let (project, report) = import(text);
+
+        assert_eq!(project.unwrap().windows[0].panes[0].command, Some("rails s".to_string()));
+        assert_eq!(report.notes.len(), 1)
+    }
+
+    #[test]
+    fn expect_erb_lines_to_be_flagged() {
+        let text = "root: ~/<%= @args[0] %>\nwindows:\n  - editor: vim\n";
+        let (project, report) = import(text);
+
+        assert_eq!(project.unwrap().root, Some("~/<%= @args[0] %>".to_string()));
+        assert_eq!(
+            report.notes,
+            vec!["Line 1 uses ERB, which muxed doesn't run. It was imported as written: root: ~/<%= @args[0] %>"]
+        )
+    }
+
+    #[test]
+    fn expect_err_without_windows() {
+        let (project, _) = import("name: empty\n");
+        assert!(project.is_err())
+    }
+}
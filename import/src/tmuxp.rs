@@ -0,0 +1,213 @@
+//! tmuxp workspace files, in YAML or JSON. ex. `~/.tmuxp/my_project.yaml`
+//!
+//! ```yaml
+//! session_name: my_project
+//! start_directory: ~/projects/my_project
+//! before_script: ./bootstrap.sh
+//! shell_command_before: source .env
+//! windows:
+//!   - window_name: editor
+//!     layout: main-vertical
+//!     focus: true
+//!     panes:
+//!       - vim
+//!       - shell_command:
+//!           - cd log
+//!           - tail -f development.log
+//! ```
+use convert::{join_commands, run_before, scalar, Report};
+use snapshot::project::{PaneEntry, Project, WindowEntry};
+use yaml_rust::Yaml;
+
+pub fn convert(doc: &Yaml, report: &mut Report) -> Result<Project, String> {
+    let settings = doc
+        .as_hash()
+        .ok_or_else(|| "This doesn't look like a tmuxp workspace.".to_string())?;
+
+    let mut project = Project::default();
+
+    for (key, value) in settings {
+        let key = scalar(key).unwrap_or_default();
+
+        match key.as_ref() {
+            "session_name" => project.name = scalar(value),
+            "start_directory" => project.root = scalar(value),
+            "before_script" => project.pre.extend(scalar(value)),
+            "shell_command_before" => project.pre_window.append(&mut shell_commands(value)),
+            "windows" => {
+                for (i, window) in value.as_vec().into_iter().flatten().enumerate() {
+                    if let Some(window) = convert_window(window, i, report) {
+                        project.windows.push(window);
+                    };
+                }
+            }
+            _ => report.unsupported(&key, "isn't supported by muxed and was left out."),
+        };
+    }
+
+    if project.windows.is_empty() {
+        return Err("The tmuxp workspace has no windows to import.".into());
+    };
+
+    Ok(project)
+}
+
+fn convert_window(window: &Yaml, position: usize, report: &mut Report) -> Option<WindowEntry> {
+    let settings = window.as_hash()?;
+    let name = match scalar(&window["window_name"]) {
+        Some(name) => name,
+        None => {
+            report.unsupported(
+                &format!("windows.{}", position),
+                "has no `window_name`, muxed windows need one. It was left out.",
+            );
+            return None;
+        }
+    };
+
+    let mut converted = WindowEntry {
+        name: name.clone(),
+        ..Default::default()
+    };
+    let mut before = vec![];
+
+    for (key, value) in settings {
+        let key = scalar(key).unwrap_or_default();
+
+        match key.as_ref() {
+            "window_name" => (),
+            "start_directory" => converted.path = scalar(value),
+            "layout" => converted.layout = scalar(value),
+            "focus" => converted.active = value.as_bool() == Some(true),
+            "shell_command_before" => before = shell_commands(value),
+            "panes" => {
+                for (i, pane) in value.as_vec().into_iter().flatten().enumerate() {
+                    converted.panes.push(convert_pane(pane, &name, i, report));
+                }
+            }
+            _ => report.unsupported(
+                &format!("windows.{}.{}", name, key),
+                "isn't supported by muxed and was left out.",
+            ),
+        };
+    }
+
+    run_before(&mut converted, &before);
+    Some(converted)
+}
+
+/// A pane is a command, or its settings with the commands in `shell_command`.
+fn convert_pane(pane: &Yaml, window: &str, position: usize, report: &mut Report) -> PaneEntry {
+    let settings = match pane.as_hash() {
+        Some(settings) => settings,
+        None => {
+            return PaneEntry {
+                command: join_commands(&shell_commands(pane)),
+                ..Default::default()
+            }
+        }
+    };
+
+    let mut converted = PaneEntry::default();
+
+    for (key, value) in settings {
+        let key = scalar(key).unwrap_or_default();
+
+        match key.as_ref() {
+            "shell_command" => converted.command = join_commands(&shell_commands(value)),
+            "start_directory" => converted.path = scalar(value),
+            "focus" => converted.active = value.as_bool() == Some(true),
+            _ => report.unsupported(
+                &format!("windows.{}.panes.{}.{}", window, position, key),
+                "isn't supported by muxed and was left out.",
+            ),
+        };
+    }
+
+    converted
+}
+
+/// Commands are a string, or a list of strings or `cmd` settings.
+/// ex. `shell_command: [{cmd: vim}]`
+fn shell_commands(yaml: &Yaml) -> Vec<String> {
+    match *yaml {
+        Yaml::Array(ref a) => a
+            .iter()
+            .filter_map(|c| scalar(c).or_else(|| scalar(&c["cmd"])))
+            .collect(),
+        _ => scalar(yaml).into_iter().collect(),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use yaml_rust::YamlLoader;
+
+    fn import(text: &str) -> (Result<Project, String>, Report) {
+        let doc = YamlLoader::load_from_str(text).unwrap();
+        let mut report = Report::default();
+        let project = convert(&doc[0], &mut report);
+        (project, report)
+    }
+
+    #[test]
+    fn expect_commands_in_every_form() {
+        let yaml = YamlLoader::load_from_str("- vim\n- cmd: guard\n").unwrap();
+        assert_eq!(shell_commands(&yaml[0]), vec!["vim", "guard"])
+    }
+
+    #[test]
+    fn expect_pane_settings_to_carry_over() {
+        let text = "
+windows:
+  - window_name: editor
+    panes:
+      - shell_command: tail -f log/development.log
+        start_directory: /var/log
+        focus: true
+";
+        let (project, _) = import(text);
+        let pane = &project.unwrap().windows[0].panes[0];
+
+        assert_eq!(pane.command, Some("tail -f log/development.log".to_string()));
+        assert_eq!(pane.path, Some("/var/log".to_string()));
+        assert!(pane.active)
+    }
+
+    #[test]
+    fn expect_windows_without_names_to_be_reported() {
+        let text = "windows:\n  - panes: [vim]\n  - window_name: logs\n";
+        let (project, report) = import(text);
+
+        assert_eq!(project.unwrap().windows.len(), 1);
+        assert!(report.notes[0].starts_with("`windows.0`"))
+    }
+
+    #[test]
+    fn expect_unsupported_keys_to_be_reported() {
+        let text = "
+global_options:
+  default-shell: /bin/zsh
+windows:
+  - window_name: editor
+    options:
+      automatic-rename: on
+    panes:
+      - shell_command: vim
+        sleep_before: 2
+";
+        let (_, report) = import(text);
+
+        assert_eq!(report.notes.len(), 3);
+        assert!(report.notes[0].starts_with("`global_options`"));
+        assert!(report.notes[1].starts_with("`windows.editor.options`"));
+        assert!(report.notes[2].starts_with("`windows.editor.panes.0.sleep_before`"))
+    }
+
+    #[test]
+    fn expect_err_without_windows() {
+        let (project, _) = import("session_name: empty\n");
+        assert!(project.is_err())
+    }
+}
@@ -0,0 +1,48 @@
+extern crate import;
+
+use import::convert;
+use import::convert::Report;
+use std::fs;
+use std::path::Path;
+
+/// Every fixture is a project file in the folder named after its format, next
+/// to the muxed project it should import as. ex. `tmuxp/split.json` and
+/// `tmuxp/split.muxed.yml`
+fn check_fixtures(format: &str) {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures").join(format);
+    let mut checked = 0;
+
+    for entry in fs::read_dir(&dir).unwrap() {
+        let file = entry.unwrap().path();
+        let name = file.file_name().unwrap().to_string_lossy().to_string();
+
+        if name.ends_with(".muxed.yml") {
+            continue;
+        };
+
+        let stem = file.file_stem().unwrap().to_string_lossy().to_string();
+        let expected = fs::read_to_string(dir.join(format!("{}.muxed.yml", stem))).unwrap();
+        let text = fs::read_to_string(&file).unwrap();
+        let project = convert(format, &text, &mut Report::default()).unwrap();
+
+        assert_eq!(project.to_yaml().unwrap(), expected, "{} imported differently", name);
+        checked += 1;
+    }
+
+    assert!(checked > 0, "No {} fixtures were found", format)
+}
+
+#[test]
+fn tmuxinator_fixtures() {
+    check_fixtures("tmuxinator")
+}
+
+#[test]
+fn tmuxp_fixtures() {
+    check_fixtures("tmuxp")
+}
+
+#[test]
+fn teamocil_fixtures() {
+    check_fixtures("teamocil")
+}
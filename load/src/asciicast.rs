@@ -0,0 +1,210 @@
+//! Asciicast v2 files, the format asciinema plays back. A header line
+//! describing the terminal, followed by a line for every event: what the
+//! terminal printed (`o`) or its new size (`r`), and when.
+//!
+//! ```text
+//! {"version": 2, "width": 80, "height": 24, "timestamp": 1589904000, "title": "muxed"}
+//! [0.248848, "o", "\u001b[1;31mHello \u001b[32mWorld!\u001b[0m\n"]
+//! [1.001376, "r", "100x30"]
+//! ```
+use std::io::{self, Write};
+use std::str;
+use std::time::Instant;
+
+/// What a recording starts with.
+/// `width`: The terminal's columns.
+/// `height`: The terminal's rows.
+/// `timestamp`: When the recording started, in seconds since the epoch.
+/// `title`: The name the recording is shown with.
+/// `env`: The `SHELL` and `TERM` it was recorded with.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Header {
+    pub width: u16,
+    pub height: u16,
+    pub timestamp: u64,
+    pub title: String,
+    pub env: Vec<(String, String)>,
+}
+
+impl Header {
+    fn to_json(&self) -> String {
+        let env = self
+            .env
+            .iter()
+            .map(|(k, v)| format!("{}: {}", json_string(k), json_string(v)))
+            .collect::<Vec<String>>()
+            .join(", ");
+
+        format!(
+            "{{\"version\": 2, \"width\": {}, \"height\": {}, \"timestamp\": {}, \"title\": {}, \"env\": {{{}}}}}",
+            self.width,
+            self.height,
+            self.timestamp,
+            json_string(&self.title),
+            env
+        )
+    }
+}
+
+/// A recording being written.
+/// `out`: Where the lines go.
+/// `start`: When the recording started, the events being timed from it.
+/// `pending`: The start of a character split between two reads, held back
+/// until the rest of it comes.
+pub struct Cast<W: Write> {
+    out: W,
+    start: Instant,
+    pending: Vec<u8>,
+}
+
+impl<W: Write> Cast<W> {
+    pub fn new(mut out: W, header: &Header) -> io::Result<Cast<W>> {
+        writeln!(out, "{}", header.to_json())?;
+
+        Ok(Cast {
+            out,
+            start: Instant::now(),
+            pending: vec![],
+        })
+    }
+
+    /// Record what the terminal printed.
+    pub fn output(&mut self, bytes: &[u8]) -> io::Result<()> {
+        self.pending.extend_from_slice(bytes);
+        let (text, rest) = complete_chars(&self.pending);
+
+        if text.is_empty() {
+            return Ok(());
+        };
+
+        self.pending = rest;
+        self.event("o", &text)
+    }
+
+    /// Record the terminal changing size.
+    pub fn resize(&mut self, width: u16, height: u16) -> io::Result<()> {
+        self.event("r", &format!("{}x{}", width, height))
+    }
+
+    fn event(&mut self, code: &str, data: &str) -> io::Result<()> {
+        let elapsed = self.start.elapsed();
+        let time = elapsed.as_secs() as f64 + f64::from(elapsed.subsec_micros()) / 1_000_000.0;
+
+        writeln!(self.out, "[{:.6}, \"{}\", {}]", time, code, json_string(data))?;
+        self.out.flush()
+    }
+}
+
+/// The text of `bytes` up to a character cut short at the end, and the bytes
+/// of that character. Anything else that isn't UTF-8 is replaced.
+fn complete_chars(bytes: &[u8]) -> (String, Vec<u8>) {
+    let mut text = String::new();
+    let mut rest = bytes;
+
+    loop {
+        match str::from_utf8(rest) {
+            Ok(valid) => {
+                text.push_str(valid);
+                return (text, vec![]);
+            }
+            Err(e) => {
+                let (valid, after) = rest.split_at(e.valid_up_to());
+                text.push_str(&String::from_utf8_lossy(valid));
+
+                match e.error_len() {
+                    Some(len) => {
+                        text.push('\u{FFFD}');
+                        rest = &after[len..];
+                    }
+                    None => return (text, after.to_vec()),
+                }
+            }
+        }
+    }
+}
+
+/// A JSON string, quoted and escaped.
+fn json_string(text: &str) -> String {
+    let mut json = String::with_capacity(text.len() + 2);
+    json.push('"');
+
+    for c in text.chars() {
+        match c {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            '\n' => json.push_str("\\n"),
+            '\r' => json.push_str("\\r"),
+            '\t' => json.push_str("\\t"),
+            c if (c as u32) < 0x20 || c == '\u{7f}' => json.push_str(&format!("\\u{:04x}", c as u32)),
+            c => json.push(c),
+        }
+    }
+
+    json.push('"');
+    json
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn header() -> Header {
+        Header {
+            width: 80,
+            height: 24,
+            timestamp: 1_589_904_000,
+            title: "muxed".to_string(),
+            env: vec![("TERM".to_string(), "xterm-256color".to_string())],
+        }
+    }
+
+    fn lines(cast: &Cast<Vec<u8>>) -> Vec<String> {
+        String::from_utf8_lossy(&cast.out).lines().map(|l| l.to_string()).collect()
+    }
+
+    #[test]
+    fn expect_a_v2_header() {
+        let cast = Cast::new(vec![], &header()).unwrap();
+
+        assert_eq!(
+            lines(&cast)[0],
+            "{\"version\": 2, \"width\": 80, \"height\": 24, \"timestamp\": 1589904000, \
+             \"title\": \"muxed\", \"env\": {\"TERM\": \"xterm-256color\"}}"
+        )
+    }
+
+    #[test]
+    fn expect_output_and_resize_events() {
+        let mut cast = Cast::new(vec![], &header()).unwrap();
+        cast.output(b"\x1b[1mhi\r\n").unwrap();
+        cast.resize(100, 30).unwrap();
+
+        let lines = lines(&cast);
+        assert!(lines[1].starts_with("[0.0"));
+        assert!(lines[1].ends_with(", \"o\", \"\\u001b[1mhi\\r\\n\"]"));
+        assert!(lines[2].ends_with(", \"r\", \"100x30\"]"))
+    }
+
+    #[test]
+    fn expect_chars_split_between_reads_to_be_kept_whole() {
+        let mut cast = Cast::new(vec![], &header()).unwrap();
+        let bytes = "é".as_bytes();
+        cast.output(&bytes[..1]).unwrap();
+        cast.output(&bytes[1..]).unwrap();
+
+        let lines = lines(&cast);
+        assert_eq!(lines.len(), 2);
+        assert!(lines[1].ends_with(", \"o\", \"é\"]"))
+    }
+
+    #[test]
+    fn expect_invalid_bytes_replaced() {
+        assert_eq!(complete_chars(b"a\xffb"), ("a\u{FFFD}b".to_string(), vec![]));
+        assert_eq!(complete_chars(b"a\xc3"), ("a".to_string(), vec![0xc3]))
+    }
+
+    #[test]
+    fn expect_quotes_and_backslashes_escaped() {
+        assert_eq!(json_string("say \"hi\" \\"), "\"say \\\"hi\\\" \\\\\"")
+    }
+}
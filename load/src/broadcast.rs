@@ -0,0 +1,247 @@
+//! Muxed exec. Type a command in to every pane of a running project, or the
+//! ones picked by window or tag. Panes are picked from the project file, so
+//! windows go by the names they were given there. Tag a window to pick all of
+//! its panes, or a pane on its own.
+//!
+//! ```yaml
+//! windows:
+//!   - api:
+//!       tags: [servers]
+//!       panes: ['rails s', 'sidekiq']
+//!   - web:
+//!       panes:
+//!         - command: npm start
+//!           tags: servers
+//!         - vim
+//! ```
+//!
+//! ```console
+//! $ muxed exec --tag servers --confirm my_project -- git pull
+//! $ muxed exec --all my_project -- clear
+//! ```
+use common::args::Args;
+use run::{self, Project};
+use std::io::{self, BufRead, Write};
+use tmux::target::{PaneTarget, Target};
+use yaml_rust::Yaml;
+
+/// Which panes a command goes to.
+/// `All`: Every pane in the project, with --all or when nothing else is picked.
+/// `Window`: The panes of a window, or a single pane as `<window>.<pane>`.
+/// `Tag`: The panes tagged, or in a window tagged.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Selector {
+    All,
+    Window(String),
+    Tag(String),
+}
+
+impl<'a> From<&'a Args> for Selector {
+    fn from(args: &'a Args) -> Self {
+        match (&args.flag_window, &args.flag_tag) {
+            _ if args.flag_all => Selector::All,
+            (Some(window), _) => Selector::Window(window.clone()),
+            (None, Some(tag)) => Selector::Tag(tag.clone()),
+            (None, None) => Selector::All,
+        }
+    }
+}
+
+pub fn exec(args: Args) -> Result<(), String> {
+    let project = Project::running(&args)?;
+    let command = args.arg_command.join(" ");
+    let panes = panes(&project, &Selector::from(&args))?;
+
+    if args.flag_confirm && !confirm(&command, &panes)? {
+        println!("Nothing was sent.");
+        return Ok(());
+    };
+
+    for (_, target) in panes {
+        run::send(&project.server, target, command.clone(), args.flag_debug)?;
+    }
+
+    Ok(())
+}
+
+/// The panes picked, each named as `<window>.<pane>`.
+fn panes(project: &Project, selector: &Selector) -> Result<Vec<(String, Target)>, String> {
+    let window_panes = |position: usize| -> Vec<(String, Target)> {
+        let (ref name, ref settings) = project.windows[position];
+        let index = (project.config.base_index + position).to_string();
+
+        (0..pane_count(settings))
+            .map(|pane| {
+                let target = PaneTarget::new(&project.session, &index, project.config.pane_base_index + pane);
+                (format!("{}.{}", name, pane), Target::PaneTarget(target))
+            })
+            .collect()
+    };
+
+    let panes: Vec<(String, Target)> = match selector {
+        Selector::All => (0..project.windows.len()).flat_map(window_panes).collect(),
+        Selector::Window(window) => {
+            let picked = run::target(&project.session, window, &project.windows, &project.config)?;
+
+            (0..project.windows.len())
+                .flat_map(window_panes)
+                .filter(|(_, target)| match picked {
+                    Target::PaneTarget(ref p) => target.arg_string() == p.arg_string,
+                    Target::WindowTarget(ref w) => target.arg_string().starts_with(&format!("{}.", w.arg_string)),
+                })
+                .collect()
+        }
+        Selector::Tag(tag) => project
+            .windows
+            .iter()
+            .enumerate()
+            .flat_map(|(position, (_, settings))| {
+                let window_tagged = tagged(settings, tag);
+
+                window_panes(position)
+                    .into_iter()
+                    .enumerate()
+                    .filter(move |(pane, _)| window_tagged || tagged(&settings["panes"][*pane], tag))
+                    .map(|(_, pane)| pane)
+            })
+            .collect(),
+    };
+
+    if panes.is_empty() {
+        return Err("No panes in the project match.".into());
+    };
+
+    Ok(panes)
+}
+
+/// How many panes a window opens. Windows without `panes` have one.
+fn pane_count(settings: &Yaml) -> usize {
+    settings["panes"].as_vec().map_or(1, |panes| panes.len().max(1))
+}
+
+/// Whether a window or pane's `tags` include the tag. Tags are a name or a
+/// list of them.
+fn tagged(settings: &Yaml, tag: &str) -> bool {
+    match settings["tags"] {
+        Yaml::String(ref t) => t == tag,
+        Yaml::Array(ref tags) => tags.iter().any(|t| t.as_str() == Some(tag)),
+        _ => false,
+    }
+}
+
+/// List the panes the command goes to and ask before sending it.
+fn confirm(command: &str, panes: &[(String, Target)]) -> Result<bool, String> {
+    println!("`{}` will be sent to:", command);
+    for (name, target) in panes {
+        println!("  {} ({})", name, target.arg_string());
+    }
+    print!("Send it? [y/N] ");
+    io::stdout().flush().map_err(|e| e.to_string())?;
+
+    let mut answer = String::new();
+    io::stdin()
+        .lock()
+        .read_line(&mut answer)
+        .map_err(|e| format!("We couldn't read the answer: {}", e))?;
+
+    Ok(answer.trim().eq_ignore_ascii_case("y") || answer.trim().eq_ignore_ascii_case("yes"))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use common::tmux::Server;
+    use project;
+    use tmux::config::Config;
+    use yaml_rust::YamlLoader;
+
+    fn project() -> Project {
+        let yaml = YamlLoader::load_from_str(
+            "windows:
+               - api:
+                   tags: [servers]
+                   panes: ['rails s', 'sidekiq']
+               - web:
+                   panes:
+                     - command: npm start
+                       tags: servers
+                     - vim
+               - logs: tail -f log/development.log",
+        )
+        .unwrap();
+
+        Project {
+            session: "app".to_string(),
+            server: Server::default(),
+            windows: project::windows(&yaml[0]).unwrap(),
+            config: Config {
+                base_index: 1,
+                ..Default::default()
+            },
+        }
+    }
+
+    fn picked(selector: Selector) -> Vec<(String, String)> {
+        panes(&project(), &selector)
+            .unwrap()
+            .into_iter()
+            .map(|(name, target)| (name, target.arg_string().to_string()))
+            .collect()
+    }
+
+    fn pair(name: &str, target: &str) -> (String, String) {
+        (name.to_string(), target.to_string())
+    }
+
+    #[test]
+    fn expect_every_pane() {
+        assert_eq!(
+            picked(Selector::All),
+            vec![
+                pair("api.0", "app:1.0"),
+                pair("api.1", "app:1.1"),
+                pair("web.0", "app:2.0"),
+                pair("web.1", "app:2.1"),
+                pair("logs.0", "app:3.0"),
+            ]
+        )
+    }
+
+    #[test]
+    fn expect_the_panes_of_a_window() {
+        assert_eq!(
+            picked(Selector::Window("web".to_string())),
+            vec![pair("web.0", "app:2.0"), pair("web.1", "app:2.1")]
+        );
+        assert_eq!(picked(Selector::Window("web.1".to_string())), vec![pair("web.1", "app:2.1")])
+    }
+
+    #[test]
+    fn expect_tagged_windows_and_panes() {
+        assert_eq!(
+            picked(Selector::Tag("servers".to_string())),
+            vec![pair("api.0", "app:1.0"), pair("api.1", "app:1.1"), pair("web.0", "app:2.0")]
+        )
+    }
+
+    #[test]
+    fn expect_selector_from_args() {
+        let all = Args {
+            flag_all: true,
+            ..Default::default()
+        };
+        let tag = Args {
+            flag_tag: Some("servers".to_string()),
+            ..Default::default()
+        };
+
+        assert_eq!(Selector::from(&all), Selector::All);
+        assert_eq!(Selector::from(&tag), Selector::Tag("servers".to_string()));
+        assert_eq!(Selector::from(&Args::default()), Selector::All)
+    }
+
+    #[test]
+    fn expect_err_when_nothing_matches() {
+        assert!(panes(&project(), &Selector::Tag("workers".to_string())).is_err())
+    }
+}
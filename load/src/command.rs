@@ -1,34 +1,59 @@
 //! The structures used to manage commands sent over to tmux.
 
+use common::tmux::Server;
+use project::supervise::{self, Policy};
+use project::wait_for::Condition;
 use std::io;
-use std::path::PathBuf;
-use std::process::Output;
+use std::os::unix::process::ExitStatusExt;
+use std::path::{Path, PathBuf};
+use std::process::{ExitStatus, Output};
 use std::rc::Rc;
+use std::thread::sleep;
+use std::time::{Duration, Instant};
 use std::{process, str};
 use tmux;
 use tmux::target::*;
+use tmux::version::{Feature, Version};
+
+/// Printed by commands creating a window, so later commands can target the
+/// new window and its first pane by id.
+static WINDOW_IDS_FORMAT: &str = "#{window_id} #{pane_id}";
+
+/// Printed by commands creating a pane.
+static PANE_ID_FORMAT: &str = "#{pane_id}";
+
+/// How often a `WaitFor` checks its condition.
+static WAIT_INTERVAL: Duration = Duration::from_millis(500);
 
 pub trait Command {
-    fn call(&self, debug: bool) -> Result<Output, io::Error> {
+    fn call(&self, server: &Server, debug: bool) -> Result<Output, io::Error> {
         if debug {
             println!("{:?}", &self.args());
         };
 
-        tmux::call(&self.args())
+        tmux::call(server, &self.args())
     }
 
     fn args(&self) -> Vec<&str>;
+
+    /// The tmux features the args rely on. These are checked against the
+    /// running version of tmux before any command is sent.
+    fn features(&self) -> Vec<Feature> {
+        vec![]
+    }
 }
 
 /// The Session command is used to fire up a new daemonized session in tmux.
 /// `name`: The Name of a named tmux session.
 /// `window_name`: The Name of the first window.
 /// `root_path`: The root directory for the tmux session.
+/// `env`: Variables the first window starts with. ex. `NAME=value`
 #[derive(Debug, Clone)]
 pub struct Session<'a> {
     pub target: SessionTarget<'a>,
     pub window_name: Rc<String>,
     pub root_path: Option<Rc<PathBuf>>,
+    pub env: Rc<Vec<String>>,
 }
 
 impl<'a> Session<'a> {
@@ -37,19 +62,51 @@ impl<'a> Session<'a> {
             target: SessionTarget::new(name),
             window_name,
             root_path,
+            env: Rc::new(vec![]),
         }
     }
+
+    pub fn with_env(mut self, env: Rc<Vec<String>>) -> Session<'a> {
+        self.env = env;
+        self
+    }
 }
 
 // TODO: Real logic exists here. Test it!
 impl<'a> Command for Session<'a> {
     fn args(&self) -> Vec<&str> {
-        let args: Vec<&str> = vec!["new", "-d", "-s", &self.target.arg_string, "-n", &self.window_name];
+        let args: Vec<&str> = vec![
+            "new",
+            "-d",
+            "-s",
+            &self.target.arg_string,
+            "-n",
+            &self.window_name,
+            "-P",
+            "-F",
+            WINDOW_IDS_FORMAT,
+        ];
 
-        match self.root_path.as_ref() {
+        let args = match self.root_path.as_ref() {
             Some(path) => [&args[..], &["-c", path.to_str().unwrap()]].concat(),
             None => args,
-        }
+        };
+
+        [args, env_args(&self.env)].concat()
+    }
+
+    fn features(&self) -> Vec<Feature> {
+        let mut features = vec![Feature::new("new-session -P -F", Version::new(1, 9, 0))];
+
+        if self.root_path.is_some() {
+            features.push(Feature::new("new-session -c", Version::new(1, 9, 0)));
+        };
+
+        if !self.env.is_empty() {
+            features.push(Feature::new("new-session -e", Version::new(3, 2, 0)));
+        };
+
+        features
     }
 }
 
@@ -60,6 +117,7 @@ impl<'a> Command for Session<'a> {
 /// `name`: The named window to be opened.
 /// `path`: An `Option<PathBuf>` containing a possible root directory passed to the
 /// `-c` arguement.
+/// `env`: Variables the window starts with. ex. `NAME=value`
 /// TODO: Turn session_name into a SessionTarget. Remove session_name_arg. Store
 /// the mutated value ':' in the SessionTarget. Convert SessionTarget from &str
 /// to Rc<String>.
@@ -69,6 +127,7 @@ pub struct Window<'a> {
     pub name: Rc<String>,
     pub path: Option<Rc<PathBuf>>,
     pub session_name_arg: String,
+    pub env: Rc<Vec<String>>,
 }
 
 impl<'a> Window<'a> {
@@ -81,21 +140,111 @@ impl<'a> Window<'a> {
             name,
             path,
             session_name_arg: name_arg,
+            env: Rc::new(vec![]),
         }
     }
+
+    pub fn with_env(mut self, env: Rc<Vec<String>>) -> Window<'a> {
+        self.env = env;
+        self
+    }
 }
 
 impl<'a> Command for Window<'a> {
     fn args(&self) -> Vec<&str> {
-        let args: Vec<&str> = vec!["new-window", "-t", &self.session_name_arg, "-n", &self.name];
+        let args: Vec<&str> = vec![
+            "new-window",
+            "-t",
+            &self.session_name_arg,
+            "-n",
+            &self.name,
+            "-P",
+            "-F",
+            WINDOW_IDS_FORMAT,
+        ];
 
-        match self.path.as_ref() {
+        let args = match self.path.as_ref() {
             Some(path) => [&args[..], &["-c", path.to_str().unwrap()]].concat(),
             None => args,
+        };
+
+        [args, env_args(&self.env)].concat()
+    }
+
+    fn features(&self) -> Vec<Feature> {
+        let mut features = vec![Feature::new("new-window -P -F", Version::new(1, 8, 0))];
+
+        if self.path.is_some() {
+            features.push(Feature::new("new-window -c", Version::new(1, 9, 0)));
+        };
+
+        if !self.env.is_empty() {
+            features.push(Feature::new("new-window -e", Version::new(3, 0, 0)));
+        };
+
+        features
+    }
+}
+
+/// An `-e` for every variable a new window starts with.
+fn env_args(env: &[String]) -> Vec<&str> {
+    env.iter().flat_map(|v| vec!["-e", v.as_str()]).collect()
+}
+
+/// Pipe everything a pane shows to a shell command, for as long as the pane
+/// is open. `-o` leaves a pipe that's already open alone.
+/// `target`: The pane.
+/// `exec`: The shell command reading the output.
+#[derive(Debug, Clone)]
+pub struct PipePane {
+    pub target: Target,
+    pub exec: String,
+}
+
+impl PipePane {
+    /// tmux runs the command through strftime first, so `%` is escaped.
+    pub fn new(target: Target, exec: String) -> PipePane {
+        PipePane {
+            target,
+            exec: exec.replace('%', "%%"),
         }
     }
 }
 
+impl Command for PipePane {
+    fn args(&self) -> Vec<&str> {
+        vec!["pipe-pane", "-o", "-t", self.target.arg_string(), &self.exec]
+    }
+}
+
+/// Set a variable in the session's environment, so panes opened later start
+/// with it too.
+/// `target`: The session.
+/// `name`: The variable.
+/// `value`: Its value.
+#[derive(Debug, Clone)]
+pub struct SetEnvironment<'a> {
+    pub target: SessionTarget<'a>,
+    pub name: String,
+    pub value: String,
+}
+
+impl<'a> SetEnvironment<'a> {
+    pub fn new(session: &'a str, name: String, value: String) -> SetEnvironment<'a> {
+        SetEnvironment {
+            target: SessionTarget::new(session),
+            name,
+            value,
+        }
+    }
+}
+
+impl<'a> Command for SetEnvironment<'a> {
+    fn args(&self) -> Vec<&str> {
+        vec!["set-environment", "-t", &self.target.arg_string, &self.name, &self.value]
+    }
+}
+
 /// The Split is used to call split-window on a particular window in the
 /// session.
 /// `target`: The target window. In the format `{session}:{window}.{paneIndex}`.
@@ -115,13 +264,29 @@ impl Split {
 
 impl Command for Split {
     fn args(&self) -> Vec<&str> {
-        let args: Vec<&str> = vec!["split-window", "-t", &self.target.arg_string];
+        let args: Vec<&str> = vec![
+            "split-window",
+            "-t",
+            &self.target.arg_string,
+            "-P",
+            "-F",
+            PANE_ID_FORMAT,
+        ];
 
         match self.path.as_ref() {
             Some(path) => [&args[..], &["-c", path.to_str().unwrap()]].concat(),
             None => args,
         }
     }
+
+    fn features(&self) -> Vec<Feature> {
+        let ids = Feature::new("split-window -P -F", Version::new(1, 8, 0));
+
+        match self.path {
+            Some(_) => vec![ids, Feature::new("split-window -c", Version::new(1, 9, 0))],
+            None => vec![ids],
+        }
+    }
 }
 
 /// The Layout command calls select-layout with a specific pre-defined tmux
@@ -169,43 +334,140 @@ impl Command for SendKeys {
     }
 }
 
+/// Replays the saved contents of a pane. The pane is restarted to print the
+/// contents, then hands over to a login shell to carry on as usual.
+/// target: The pane to replay in.
+/// path: The directory the pane works in, it's lost on restarting otherwise.
+/// exec: The shell command printing the contents.
+#[derive(Debug, Clone)]
+pub struct Contents {
+    pub target: PaneTarget,
+    pub path: Option<Rc<PathBuf>>,
+    pub exec: String,
+}
+
+impl Contents {
+    pub fn new(target: PaneTarget, path: Option<Rc<PathBuf>>, file: &Path) -> Contents {
+        let file = file.to_string_lossy().replace('\'', "'\\''");
+        let exec = format!("cat '{}'; exec \"$SHELL\" -l", file);
+
+        Contents { target, path, exec }
+    }
+}
+
+impl Command for Contents {
+    fn args(&self) -> Vec<&str> {
+        let args: Vec<&str> = vec!["respawn-pane", "-k", "-t", &self.target.arg_string];
+
+        match self.path.as_ref() {
+            Some(path) => [&args[..], &["-c", path.to_str().unwrap()], &[&self.exec]].concat(),
+            None => [&args[..], &[&self.exec]].concat(),
+        }
+    }
+
+    fn features(&self) -> Vec<Feature> {
+        match self.path {
+            Some(_) => vec![Feature::new("respawn-pane -c", Version::new(2, 6, 0))],
+            None => vec![],
+        }
+    }
+}
+
+/// How the user is handed over to their session once it's built.
+/// `Attach`: Attach a new tmux client in the current terminal.
+/// `Switch`: Switch the tmux client we're running inside of to the session.
+/// `Print`: Don't attach, only print the session name.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AttachMode {
+    Attach,
+    Switch,
+    Print,
+}
+
+impl AttachMode {
+    /// Outside of tmux we always attach a new client. Inside of tmux attaching
+    /// would nest clients, so we switch the current client over unless the
+    /// `nested` option asks for a `new` client or to `print` the name instead.
+    pub fn new(nested: Option<&str>, inside_tmux: bool) -> Result<AttachMode, String> {
+        if !inside_tmux {
+            return Ok(AttachMode::Attach);
+        };
+
+        match nested.unwrap_or("switch") {
+            "switch" => Ok(AttachMode::Switch),
+            "new" => Ok(AttachMode::Attach),
+            "print" => Ok(AttachMode::Print),
+            x => Err(format!(
+                "`{}` isn't a known --nested option. Try one of: switch, new or print.",
+                x
+            )),
+        }
+    }
+}
+
 /// Used to attach to the daemonized session.
 /// name: The named session to attach too.
 /// `path`: An `Option<PathBuf>` containing a possible root directory passed to the
 /// `-c` arguement.
+/// `mode`: How to attach. See `AttachMode`.
 #[derive(Debug, Clone)]
 pub struct Attach<'a> {
     pub name: SessionTarget<'a>,
     pub root_path: Option<Rc<PathBuf>>,
+    pub mode: AttachMode,
 }
 
 impl<'a> Attach<'a> {
-    pub fn new(name: &'a str, root_path: Option<Rc<PathBuf>>) -> Attach<'a> {
+    pub fn new(name: &'a str, root_path: Option<Rc<PathBuf>>, mode: AttachMode) -> Attach<'a> {
         Attach {
             name: SessionTarget::new(name),
             root_path,
+            mode,
         }
     }
 }
 
 impl<'a> Command for Attach<'a> {
     fn args(&self) -> Vec<&str> {
+        if self.mode == AttachMode::Switch {
+            return vec!["switch-client", "-t", &self.name.arg_string];
+        };
+
         let args: Vec<&str> = vec!["attach", "-t", &self.name.arg_string];
 
-        let args = match self.root_path.as_ref() {
+        match self.root_path.as_ref() {
             Some(path) => [&args[..], &["-c", path.to_str().unwrap()]].concat(),
             None => args,
-        };
-
-        [&args[..], &[">/dev/null"]].concat()
+        }
     }
 
-    fn call(&self, debug: bool) -> Result<Output, io::Error> {
+    fn call(&self, server: &Server, debug: bool) -> Result<Output, io::Error> {
         if debug {
             println!("{:?}", &self.args());
         };
 
-        tmux::attach(&self.args())
+        match self.mode {
+            AttachMode::Attach => tmux::attach(server, &self.args()),
+            AttachMode::Switch => tmux::call(server, &self.args()),
+            AttachMode::Print => {
+                println!("{}", self.name);
+
+                Ok(Output {
+                    status: ExitStatus::from_raw(0),
+                    stdout: vec![],
+                    stderr: vec![],
+                })
+            }
+        }
+    }
+
+    fn features(&self) -> Vec<Feature> {
+        match (self.mode, self.root_path.as_ref()) {
+            (AttachMode::Attach, Some(_)) => {
+                vec![Feature::new("attach-session -c", Version::new(1, 9, 0))]
+            }
+            _ => vec![],
+        }
     }
 }
 
@@ -267,7 +529,7 @@ impl Command for Pre {
         vec![]
     }
 
-    fn call(&self, debug: bool) -> Result<Output, io::Error> {
+    fn call(&self, _server: &Server, debug: bool) -> Result<Output, io::Error> {
         if debug {
             println!("{:?}", &self.exec);
         };
@@ -281,6 +543,183 @@ impl Command for Pre {
     }
 }
 
+/// Run a pane's command in place of its shell, and keep it running. The pane
+/// stays open when the command exits, with the restart policy in its options
+/// for the session's `pane-died` hook to read.
+/// `target`: The pane.
+/// `exec`: The command.
+/// `path`: The directory to run it in.
+/// `options`: The pane options holding the policy.
+#[derive(Debug, Clone)]
+pub struct Supervise {
+    pub target: PaneTarget,
+    pub exec: String,
+    pub path: Option<Rc<PathBuf>>,
+    pub options: Vec<(&'static str, String)>,
+}
+
+impl Supervise {
+    pub fn new(target: PaneTarget, exec: String, path: Option<Rc<PathBuf>>, policy: &Policy) -> Supervise {
+        let mut options = vec![
+            (supervise::RESTART_OPTION, policy.restart.as_str().to_string()),
+            (supervise::DELAY_OPTION, policy.delay.as_millis().to_string()),
+            (supervise::COUNT_OPTION, "0".to_string()),
+        ];
+
+        if let Some(max) = policy.max_restarts {
+            options.push((supervise::MAX_OPTION, max.to_string()));
+        };
+
+        Supervise {
+            target,
+            exec,
+            path,
+            options,
+        }
+    }
+}
+
+impl Command for Supervise {
+    fn args(&self) -> Vec<&str> {
+        let target = self.target.arg_string.as_str();
+        let mut args = vec!["set-option", "-p", "-t", target, "remain-on-exit", "on"];
+
+        for (option, value) in &self.options {
+            args.extend_from_slice(&[";", "set-option", "-p", "-t", target, option, value]);
+        }
+
+        args.extend_from_slice(&[";", "respawn-pane", "-k", "-t", target]);
+
+        if let Some(path) = self.path.as_ref() {
+            args.extend_from_slice(&["-c", path.to_str().unwrap()]);
+        };
+
+        args.push(&self.exec);
+        args
+    }
+
+    fn features(&self) -> Vec<Feature> {
+        vec![Feature::new("set-option -p", Version::new(3, 0, 0))]
+    }
+}
+
+/// Set a hook on the session, running a tmux command when the event happens.
+/// `target`: The session.
+/// `hook`: The event. ex. `pane-died`
+/// `command`: The tmux command to run.
+#[derive(Debug, Clone)]
+pub struct SetHook<'a> {
+    pub target: SessionTarget<'a>,
+    pub hook: &'static str,
+    pub command: String,
+}
+
+impl<'a> SetHook<'a> {
+    pub fn new(session: &'a str, hook: &'static str, command: String) -> SetHook<'a> {
+        SetHook {
+            target: SessionTarget::new(session),
+            hook,
+            command,
+        }
+    }
+}
+
+impl<'a> Command for SetHook<'a> {
+    fn args(&self) -> Vec<&str> {
+        vec!["set-hook", "-t", &self.target.arg_string, self.hook, &self.command]
+    }
+}
+
+/// Hold a pane's commands back until a condition is ready. Nothing is sent to
+/// tmux, the condition is checked until it's ready or the time runs out.
+/// `target`: The pane waiting. Used to name it, so it's never resolved to an id.
+/// `condition`: What the pane waits for.
+/// `timeout`: How long to wait before giving up on starting the pane.
+/// `path`: The pane's directory. Files and commands are relative to it.
+/// `then`: The commands held back, run once the condition is ready and left
+/// out when it times out.
+#[derive(Debug, Clone)]
+pub struct WaitFor<'a> {
+    pub target: PaneTarget,
+    pub condition: Condition,
+    pub timeout: Duration,
+    pub path: Option<Rc<PathBuf>>,
+    pub then: Vec<Commands<'a>>,
+}
+
+impl<'a> WaitFor<'a> {
+    pub fn new(
+        target: PaneTarget,
+        condition: Condition,
+        timeout: Duration,
+        path: Option<Rc<PathBuf>>,
+        then: Vec<Commands<'a>>,
+    ) -> WaitFor<'a> {
+        WaitFor {
+            target,
+            condition,
+            timeout,
+            path,
+            then,
+        }
+    }
+}
+
+impl<'a> Command for WaitFor<'a> {
+    fn args(&self) -> Vec<&str> {
+        // No-op!
+        vec![]
+    }
+
+    fn call(&self, server: &Server, debug: bool) -> Result<Output, io::Error> {
+        if debug {
+            println!("{:?}", &self);
+        };
+
+        let started = Instant::now();
+        let ready = || self.condition.ready(server, &self.target.session, &self.path);
+
+        if !ready() {
+            println!("Waiting for {} before starting {}", self.condition, self.target);
+
+            while !ready() {
+                if started.elapsed() >= self.timeout {
+                    return Err(io::Error::new(
+                        io::ErrorKind::TimedOut,
+                        format!(
+                            "Gave up waiting for {} after {:?}, so {} wasn't started.",
+                            self.condition,
+                            self.timeout,
+                            self.target
+                        ),
+                    ));
+                };
+
+                sleep(WAIT_INTERVAL);
+            }
+        };
+
+        Ok(Output {
+            status: ExitStatus::from_raw(0),
+            stdout: vec![],
+            stderr: vec![],
+        })
+    }
+
+    fn features(&self) -> Vec<Feature> {
+        let mut features = match self.condition {
+            Condition::PaneOutput(..) => vec![Feature::new("capture-pane -p", Version::new(1, 8, 0))],
+            _ => vec![],
+        };
+
+        for command in &self.then {
+            features.append(&mut command.as_trait().features());
+        }
+
+        features
+    }
+}
+
 /// The Command enum. Commands represent the series of commands sent to the
 /// running tmux process to build a users env. This is an enum to support
 /// containing all the commands that require running in a single Vec. This
@@ -288,13 +727,19 @@ impl Command for Pre {
 #[derive(Debug, Clone)]
 pub enum Commands<'a> {
     Attach(Attach<'a>),
+    Contents(Contents),
     Layout(Layout),
+    PipePane(PipePane),
     Pre(Pre),
     SelectPane(SelectPane),
     SelectWindow(SelectWindow),
     SendKeys(SendKeys),
     Session(Session<'a>),
+    SetEnvironment(SetEnvironment<'a>),
+    SetHook(SetHook<'a>),
     Split(Split),
+    Supervise(Supervise),
+    WaitFor(WaitFor<'a>),
     Window(Window<'a>),
 }
 
@@ -302,16 +747,58 @@ impl<'a> Commands<'a> {
     pub fn as_trait(&self) -> &dyn Command {
         match self {
             Commands::Attach(c) => c,
+            Commands::Contents(c) => c,
             Commands::Layout(c) => c,
+            Commands::PipePane(c) => c,
             Commands::Pre(c) => c,
             Commands::SelectPane(c) => c,
             Commands::SelectWindow(c) => c,
             Commands::SendKeys(c) => c,
             Commands::Session(c) => c,
+            Commands::SetEnvironment(c) => c,
+            Commands::SetHook(c) => c,
             Commands::Split(c) => c,
+            Commands::Supervise(c) => c,
+            Commands::WaitFor(c) => c,
             Commands::Window(c) => c,
         }
     }
+
+    /// A copy of the command with its targets pointed at the ids of windows
+    /// and panes created by earlier commands.
+    pub fn resolve(&self, ids: &Ids) -> Commands<'a> {
+        let mut command = self.clone();
+
+        match command {
+            Commands::Contents(ref mut c) => ids.resolve_pane(&mut c.target),
+            Commands::Layout(ref mut c) => ids.resolve_window(&mut c.target),
+            Commands::PipePane(ref mut c) => ids.resolve(&mut c.target),
+            Commands::SelectPane(ref mut c) => ids.resolve_pane(&mut c.target),
+            Commands::SelectWindow(ref mut c) => ids.resolve_window(&mut c.target),
+            Commands::SendKeys(ref mut c) => ids.resolve(&mut c.target),
+            Commands::Split(ref mut c) => ids.resolve_pane(&mut c.target),
+            Commands::Supervise(ref mut c) => ids.resolve_pane(&mut c.target),
+            _ => (),
+        };
+
+        command
+    }
+
+    /// Feed the output of a command forward. Commands creating windows or
+    /// panes print the new ids, which are recorded for the commands after.
+    pub fn record(&self, output: &Output, ids: &mut Ids) {
+        let stdout = String::from_utf8_lossy(&output.stdout);
+
+        match self {
+            Commands::Session(_) | Commands::Window(_) => ids.add_window(&stdout),
+            Commands::Split(c) => {
+                if let Some((window, pane)) = c.target.position {
+                    ids.add_pane((window, pane + 1), &stdout);
+                };
+            }
+            _ => (),
+        };
+    }
 }
 
 impl<'a> From<Attach<'a>> for Commands<'a> {
@@ -320,12 +807,24 @@ impl<'a> From<Attach<'a>> for Commands<'a> {
     }
 }
 
+impl<'a> From<Contents> for Commands<'a> {
+    fn from(command: Contents) -> Self {
+        Commands::Contents(command)
+    }
+}
+
 impl<'a> From<Layout> for Commands<'a> {
     fn from(command: Layout) -> Self {
         Commands::Layout(command)
     }
 }
 
+impl<'a> From<PipePane> for Commands<'a> {
+    fn from(command: PipePane) -> Self {
+        Commands::PipePane(command)
+    }
+}
+
 impl<'a> From<Pre> for Commands<'a> {
     fn from(command: Pre) -> Self {
         Commands::Pre(command)
@@ -356,14 +855,211 @@ impl<'a> From<Session<'a>> for Commands<'a> {
     }
 }
 
+impl<'a> From<SetEnvironment<'a>> for Commands<'a> {
+    fn from(command: SetEnvironment<'a>) -> Self {
+        Commands::SetEnvironment(command)
+    }
+}
+
+impl<'a> From<SetHook<'a>> for Commands<'a> {
+    fn from(command: SetHook<'a>) -> Self {
+        Commands::SetHook(command)
+    }
+}
+
 impl<'a> From<Split> for Commands<'a> {
     fn from(command: Split) -> Self {
         Commands::Split(command)
     }
 }
 
+impl<'a> From<Supervise> for Commands<'a> {
+    fn from(command: Supervise) -> Self {
+        Commands::Supervise(command)
+    }
+}
+
+impl<'a> From<WaitFor<'a>> for Commands<'a> {
+    fn from(command: WaitFor<'a>) -> Self {
+        Commands::WaitFor(command)
+    }
+}
+
 impl<'a> From<Window<'a>> for Commands<'a> {
     fn from(command: Window<'a>) -> Self {
         Commands::Window(command)
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use project::supervise::Restart;
+
+    #[test]
+    fn expect_attach_outside_of_tmux() {
+        let mode = AttachMode::new(Some("print"), false);
+        assert_eq!(mode, Ok(AttachMode::Attach))
+    }
+
+    #[test]
+    fn expect_switch_inside_of_tmux_by_default() {
+        let mode = AttachMode::new(None, true);
+        assert_eq!(mode, Ok(AttachMode::Switch))
+    }
+
+    #[test]
+    fn expect_attach_inside_of_tmux_with_new() {
+        let mode = AttachMode::new(Some("new"), true);
+        assert_eq!(mode, Ok(AttachMode::Attach))
+    }
+
+    #[test]
+    fn expect_print_inside_of_tmux_with_print() {
+        let mode = AttachMode::new(Some("print"), true);
+        assert_eq!(mode, Ok(AttachMode::Print))
+    }
+
+    #[test]
+    fn expect_err_with_unknown_nested_option() {
+        let mode = AttachMode::new(Some("nest"), true);
+        assert!(mode.is_err())
+    }
+
+    #[test]
+    fn expect_switch_client_args() {
+        let attach = Attach::new("muxed", Some(Rc::new(PathBuf::from("/tmp"))), AttachMode::Switch);
+        assert_eq!(attach.args(), vec!["switch-client", "-t", "muxed"])
+    }
+
+    #[test]
+    fn expect_attach_args_with_root() {
+        let attach = Attach::new("muxed", Some(Rc::new(PathBuf::from("/tmp"))), AttachMode::Attach);
+        assert_eq!(attach.args(), vec!["attach", "-t", "muxed", "-c", "/tmp"])
+    }
+
+    #[test]
+    fn expect_window_with_path_to_require_1_9() {
+        let window = Window::new("muxed", Rc::new("vim".to_string()), Some(Rc::new(PathBuf::from("/tmp"))));
+        let features = window.features();
+        assert!(features.iter().any(|f| f.check(&Version::new(1, 8, 0)).is_err()))
+    }
+
+    #[test]
+    fn expect_window_without_path_to_require_1_8() {
+        let window = Window::new("muxed", Rc::new("vim".to_string()), None);
+        assert!(window.features()[0].check(&Version::new(1, 7, 0)).is_err());
+        assert!(window.features()[0].check(&Version::new(1, 8, 0)).is_ok())
+    }
+
+    #[test]
+    fn expect_window_env_to_be_passed_with_e() {
+        let env = Rc::new(vec!["PORT=3000".to_string(), "NAME=my app".to_string()]);
+        let window = Window::new("muxed", Rc::new("vim".to_string()), None).with_env(env);
+
+        assert_eq!(
+            &window.args()[8..],
+            &["-e", "PORT=3000", "-e", "NAME=my app"]
+        );
+        assert!(window.features().iter().any(|f| f.check(&Version::new(2, 9, 0)).is_err()))
+    }
+
+    #[test]
+    fn expect_session_env_to_require_3_2() {
+        let env = Rc::new(vec!["PORT=3000".to_string()]);
+        let session = Session::new("muxed", Rc::new("vim".to_string()), None).with_env(env);
+
+        assert!(session.args().ends_with(&["-e", "PORT=3000"]));
+        assert!(session.features().iter().any(|f| f.check(&Version::new(3, 1, 0)).is_err()))
+    }
+
+    #[test]
+    fn expect_split_to_record_the_next_pane() {
+        let split: Commands = Split::new(PaneTarget::new("muxed", "vim", 0).at(2, 0), None).into();
+        let output = Output {
+            status: ExitStatus::from_raw(0),
+            stdout: b"%12\n".to_vec(),
+            stderr: vec![],
+        };
+        let mut ids = Ids::default();
+        split.record(&output, &mut ids);
+
+        assert_eq!(ids.panes.get(&(2, 1)), Some(&"%12".to_string()))
+    }
+
+    #[test]
+    fn expect_send_keys_to_resolve_to_pane_id() {
+        let mut ids = Ids::default();
+        ids.add_window("@1 %1");
+        ids.add_pane((0, 1), "%3");
+
+        let target = Target::PaneTarget(PaneTarget::new("muxed", "vi.m", 1).at(0, 1));
+        let send_keys: Commands = SendKeys::new(target, "ls".to_string()).into();
+
+        match send_keys.resolve(&ids) {
+            Commands::SendKeys(c) => assert_eq!(c.target.arg_string(), "%3"),
+            _ => panic!("Expected SendKeys"),
+        }
+    }
+
+    #[test]
+    fn expect_supervise_to_keep_the_pane_and_respawn_it() {
+        let policy = Policy {
+            restart: Restart::OnFailure,
+            delay: Duration::from_secs(2),
+            max_restarts: Some(5),
+        };
+        let target = PaneTarget::new("muxed", "server", 0);
+        let supervise = Supervise::new(target, "rails s".to_string(), None, &policy);
+
+        assert_eq!(
+            supervise.args(),
+            vec![
+                "set-option", "-p", "-t", "muxed:server.0", "remain-on-exit", "on", ";",
+                "set-option", "-p", "-t", "muxed:server.0", "@muxed_restart", "on-failure", ";",
+                "set-option", "-p", "-t", "muxed:server.0", "@muxed_restart_delay", "2000", ";",
+                "set-option", "-p", "-t", "muxed:server.0", "@muxed_restarts", "0", ";",
+                "set-option", "-p", "-t", "muxed:server.0", "@muxed_max_restarts", "5", ";",
+                "respawn-pane", "-k", "-t", "muxed:server.0", "rails s",
+            ]
+        )
+    }
+
+    #[test]
+    fn expect_pipe_pane_to_escape_strftime() {
+        let target = Target::PaneTarget(PaneTarget::new("muxed", "vim", 0));
+        let pipe = PipePane::new(target, "date '+%H:%M' >> /tmp/log".to_string());
+
+        assert_eq!(pipe.args(), vec!["pipe-pane", "-o", "-t", "muxed:vim.0", "date '+%%H:%%M' >> /tmp/log"])
+    }
+
+    #[test]
+    fn expect_wait_for_to_report_the_condition_on_timeout() {
+        let target = PaneTarget::new("muxed", "app", 0);
+        let condition = Condition::Command("false".to_string());
+        let wait = WaitFor::new(target, condition, Duration::from_millis(10), None, vec![]);
+        let err = wait.call(&Server::default(), false).unwrap_err();
+
+        assert_eq!(err.to_string(), "Gave up waiting for `false` to succeed after 10ms, so muxed:app.0 wasn't started.")
+    }
+
+    #[test]
+    fn expect_contents_to_quote_the_file() {
+        let target = PaneTarget::new("muxed", "vim", 0);
+        let contents = Contents::new(target, None, Path::new("/tmp/it's/0.0"));
+
+        assert_eq!(contents.exec, "cat '/tmp/it'\\''s/0.0'; exec \"$SHELL\" -l")
+    }
+
+    #[test]
+    fn expect_contents_to_keep_the_pane_path() {
+        let target = PaneTarget::new("muxed", "vim", 0);
+        let path = Some(Rc::new(PathBuf::from("/tmp")));
+        let contents = Contents::new(target, path, Path::new("/tmp/0.0"));
+
+        assert_eq!(
+            contents.args(),
+            vec!["respawn-pane", "-k", "-t", "muxed:vim.0", "-c", "/tmp", &contents.exec]
+        )
+    }
+}
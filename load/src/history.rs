@@ -0,0 +1,346 @@
+//! The projects opened recently. Every launch is kept as a line in the
+//! `history` file of `~/.muxed/`, with the project's root and the options it
+//! was opened with, so `muxed last` can open it again the same way. Launches
+//! are kept there even for projects read from another directory with -p.
+//! Only the latest launches are kept.
+//!
+//! ```console
+//! $ muxed recent
+//! my_project  2 minutes ago  ~/src/my_project  --worktree feature/login
+//! notes       3 days ago     ~/notes
+//! ```
+use common::args::Args;
+use common::project_paths::{project_paths, ProjectPaths};
+use std::fs;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// The file in the config directory launches are kept in.
+static HISTORY_FILE: &str = "history";
+
+/// How many launches are kept.
+static KEEP: usize = 100;
+
+/// A project being opened.
+/// `time`: When, in seconds since the epoch.
+/// `project`: The project file opened.
+/// `root`: The directory the project opened in, when it has one.
+/// `options`: The options it was opened with, as they're given on the
+/// command line. ex. `["--worktree", "feature/login", "-d"]`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Launch {
+    pub time: u64,
+    pub project: String,
+    pub root: Option<PathBuf>,
+    pub options: Vec<String>,
+}
+
+impl Launch {
+    pub fn new(args: &Args, root: Option<PathBuf>) -> Launch {
+        let time = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+
+        Launch {
+            time,
+            project: args.arg_project.clone(),
+            root,
+            options: options(args),
+        }
+    }
+
+    /// The args to open the project with again. Options given now are kept,
+    /// the ones it was opened with are added.
+    pub fn args(&self, args: Args) -> Args {
+        let mut args = Args {
+            arg_project: self.project.clone(),
+            ..args
+        };
+
+        let mut options = self.options.iter();
+        while let Some(option) = options.next() {
+            match option.as_str() {
+                "-d" => args.flag_d = true,
+                "--with-contents" => args.flag_with_contents = true,
+                flag => {
+                    let value = options.next().cloned();
+                    match flag {
+                        "-p" => args.flag_p = value,
+                        "--nested" => args.flag_nested = value,
+                        "--socket-name" => args.flag_socket_name = value,
+                        "--socket-path" => args.flag_socket_path = value,
+                        "--tmux-command" => args.flag_tmux_command = value,
+                        "--tmux-config" => args.flag_tmux_config = value,
+                        "--worktree" => args.flag_worktree = value,
+                        _ => {}
+                    }
+                }
+            };
+        }
+
+        args
+    }
+
+    fn to_line(&self) -> String {
+        let root = self.root.as_ref().map(|r| r.to_string_lossy().to_string());
+        let fields: Vec<String> = vec![self.time.to_string(), self.project.clone(), root.unwrap_or_default()]
+            .into_iter()
+            .chain(self.options.iter().cloned())
+            .map(|field| field.replace(&['\t', '\n'][..], " "))
+            .collect();
+
+        fields.join("\t")
+    }
+
+    fn from_line(line: &str) -> Option<Launch> {
+        let mut fields = line.split('\t');
+        let time = fields.next()?.parse().ok()?;
+        let project = fields.next().filter(|p| !p.is_empty())?.to_string();
+        let root = fields.next().filter(|r| !r.is_empty()).map(PathBuf::from);
+
+        Some(Launch {
+            time,
+            project,
+            root,
+            options: fields.map(|o| o.to_string()).collect(),
+        })
+    }
+}
+
+/// Keep a launch, dropping the oldest ones past `KEEP`.
+pub fn record(project_paths: &ProjectPaths, launch: &Launch) -> Result<(), String> {
+    let file = project_paths.config_directory().join(HISTORY_FILE);
+    let mut launches = read(&file)?;
+    launches.push(launch.clone());
+
+    let skip = launches.len().saturating_sub(KEEP);
+    let lines: Vec<String> = launches.iter().skip(skip).map(|l| l.to_line()).collect();
+
+    fs::write(&file, format!("{}\n", lines.join("\n")))
+        .map_err(|e| format!("We couldn't write the history to {}: {}", file.display(), e))
+}
+
+/// The launches in the history, oldest first.
+pub fn launches(project_paths: &ProjectPaths) -> Result<Vec<Launch>, String> {
+    read(&project_paths.config_directory().join(HISTORY_FILE))
+}
+
+/// The latest launch of every project, newest first.
+pub fn latest(launches: &[Launch]) -> Vec<&Launch> {
+    let mut latest: Vec<&Launch> = vec![];
+
+    for launch in launches.iter().rev() {
+        if !latest.iter().any(|l| l.project == launch.project) {
+            latest.push(launch);
+        };
+    }
+
+    latest
+}
+
+/// Order projects by when they were last opened, newest first. Projects
+/// never opened go last, in the order they were in.
+pub fn sort_by_recency(projects: &mut [String], launches: &[Launch]) {
+    let latest = latest(launches);
+    let rank = |project: &String| latest.iter().position(|l| &l.project == project).unwrap_or(latest.len());
+
+    projects.sort_by_key(|p| rank(p));
+}
+
+/// Muxed recent. List the projects opened, newest first.
+pub fn recent(args: Args) -> Result<(), String> {
+    let launches = launches(&project_paths(&args))?;
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+
+    print!("{}", render(&latest(&launches), now));
+    Ok(())
+}
+
+/// Muxed last. Open the project opened last, with the same options.
+pub fn last(args: Args) -> Result<(), String> {
+    let launches = launches(&project_paths(&args))?;
+    let launch = launches
+        .last()
+        .ok_or_else(|| "No projects have been opened yet.".to_string())?;
+
+    ::exec(launch.args(args))
+}
+
+fn read(file: &Path) -> Result<Vec<Launch>, String> {
+    match fs::read_to_string(file) {
+        Ok(contents) => Ok(contents.lines().filter_map(Launch::from_line).collect()),
+        Err(ref e) if e.kind() == ErrorKind::NotFound => Ok(vec![]),
+        Err(e) => Err(format!("We couldn't read the history in {}: {}", file.display(), e)),
+    }
+}
+
+/// The options worth opening a project with again.
+fn options(args: &Args) -> Vec<String> {
+    let mut options = vec![];
+
+    let values = vec![
+        ("-p", &args.flag_p),
+        ("--nested", &args.flag_nested),
+        ("--socket-name", &args.flag_socket_name),
+        ("--socket-path", &args.flag_socket_path),
+        ("--tmux-command", &args.flag_tmux_command),
+        ("--tmux-config", &args.flag_tmux_config),
+        ("--worktree", &args.flag_worktree),
+    ];
+
+    for (flag, value) in values {
+        if let Some(value) = value {
+            options.push(flag.to_string());
+            options.push(value.clone());
+        };
+    }
+
+    if args.flag_d {
+        options.push("-d".to_string());
+    };
+
+    if args.flag_with_contents {
+        options.push("--with-contents".to_string());
+    };
+
+    options
+}
+
+/// A line per launch, in columns.
+fn render(launches: &[&Launch], now: u64) -> String {
+    let rows: Vec<(String, String, String, String)> = launches
+        .iter()
+        .map(|l| {
+            let root = l.root.as_ref().map(|r| r.display().to_string()).unwrap_or_default();
+            (l.project.clone(), ago(now.saturating_sub(l.time)), root, l.options.join(" "))
+        })
+        .collect();
+
+    let project_width = rows.iter().map(|r| r.0.chars().count()).max().unwrap_or(0);
+    let ago_width = rows.iter().map(|r| r.1.chars().count()).max().unwrap_or(0);
+
+    rows.iter()
+        .map(|(project, ago, root, options)| {
+            let line = format!(
+                "{:pw$}  {:aw$}  {}  {}",
+                project,
+                ago,
+                root,
+                options,
+                pw = project_width,
+                aw = ago_width
+            );
+            format!("{}\n", line.trim_end())
+        })
+        .collect()
+}
+
+/// How long ago, in the largest whole unit.
+fn ago(seconds: u64) -> String {
+    let units = [(86_400, "day"), (3_600, "hour"), (60, "minute")];
+
+    for (size, unit) in units.iter() {
+        let count = seconds / size;
+
+        if count == 1 {
+            return format!("1 {} ago", unit);
+        } else if count > 1 {
+            return format!("{} {}s ago", count, unit);
+        };
+    }
+
+    "just now".to_string()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use common::rand_names;
+
+    fn launch(time: u64, project: &str) -> Launch {
+        Launch {
+            time,
+            project: project.to_string(),
+            root: None,
+            options: vec![],
+        }
+    }
+
+    #[test]
+    fn expect_options_to_open_the_same_way_again() {
+        let args = Args {
+            arg_project: "api".to_string(),
+            flag_d: false,
+            flag_p: Some("/src/projects".to_string()),
+            flag_worktree: Some("feature/login".to_string()),
+            ..Default::default()
+        };
+        let launch = Launch::new(&args, Some(PathBuf::from("/src/api")));
+
+        assert_eq!(launch.options, vec!["-p", "/src/projects", "--worktree", "feature/login"]);
+
+        let again = launch.args(Args {
+            arg_project: String::new(),
+            flag_d: true,
+            ..Default::default()
+        });
+        assert_eq!(again.arg_project, "api");
+        assert_eq!(again.flag_p, Some("/src/projects".to_string()));
+        assert_eq!(again.flag_worktree, Some("feature/login".to_string()));
+        assert!(again.flag_d)
+    }
+
+    #[test]
+    fn expect_lines_to_read_back() {
+        let launch = Launch {
+            time: 1_589_904_000,
+            project: "api".to_string(),
+            root: Some(PathBuf::from("/src/api")),
+            options: vec!["-d".to_string()],
+        };
+
+        assert_eq!(launch.to_line(), "1589904000\tapi\t/src/api\t-d");
+        assert_eq!(Launch::from_line(&launch.to_line()), Some(launch));
+        assert_eq!(Launch::from_line("not a launch"), None)
+    }
+
+    #[test]
+    fn expect_only_the_latest_launches_kept() {
+        let dir = rand_names::project_path();
+        let projects = PathBuf::from("/tmp/projects");
+        let project_paths = ProjectPaths::new(dir.clone(), projects.clone(), projects.join("api.yml"));
+        let _ = fs::create_dir_all(project_paths.config_directory());
+
+        for time in 0..(KEEP as u64 + 5) {
+            record(&project_paths, &launch(time, "api")).unwrap();
+        }
+        let kept = launches(&project_paths);
+        let _ = fs::remove_dir_all(&dir);
+
+        let kept = kept.unwrap();
+        assert_eq!(kept.len(), KEEP);
+        assert_eq!(kept[0].time, 5)
+    }
+
+    #[test]
+    fn expect_projects_by_their_latest_launch() {
+        let launches = vec![launch(1, "api"), launch(2, "notes"), launch(3, "api")];
+        let mut projects: Vec<String> = ["blog", "notes", "api"].iter().map(|p| p.to_string()).collect();
+        sort_by_recency(&mut projects, &launches);
+
+        assert_eq!(projects, vec!["api", "notes", "blog"]);
+        assert_eq!(render(&latest(&launches), 7_203), "api    2 hours ago\nnotes  2 hours ago\n")
+    }
+
+    #[test]
+    fn expect_time_ago_in_whole_units() {
+        assert_eq!(ago(30), "just now");
+        assert_eq!(ago(60), "1 minute ago");
+        assert_eq!(ago(3 * 86_400 + 50), "3 days ago")
+    }
+}
@@ -4,36 +4,74 @@ extern crate yaml_rust;
 
 extern crate common;
 
+pub mod asciicast;
+pub mod broadcast;
 pub mod command;
+pub mod history;
+pub mod list;
+pub mod pick;
 pub mod project;
+pub mod record;
+pub mod restart;
+pub mod run;
+pub mod terminal;
 pub mod tmux;
 
 use args::Args;
-use command::Commands;
+use command::{AttachMode, Commands};
 use common::project_paths::project_paths;
+use common::tmux::Server;
 use common::{args, first_run};
-use project::parser;
+use history::Launch;
+use project::{parser, worktree};
+use std::io;
 use tmux::config::Config;
+use tmux::target::Ids;
 
 pub fn exec(args: Args) -> Result<(), String> {
+    open(args, true).map(|_| ())
+}
+
+/// Open the project, returning the session it's in and the server it runs on.
+/// Without `attach` the session is left in the background, whatever -d says.
+pub fn open(args: Args, attach: bool) -> Result<(String, Server), String> {
     let project_paths = project_paths(&args);
 
-    // FIXME: Should have an error message
-    let yaml = project::read(&args.arg_project, &project_paths).unwrap();
+    let mut yaml = project::read(&args.arg_project, &project_paths)?;
+
+    if let Some(ref branch) = args.flag_worktree {
+        worktree::checkout(&mut yaml[0], &args.arg_project, branch)?;
+    };
+
     let project_name = &yaml[0]["name"]
         .as_str()
         .unwrap_or(&args.arg_project)
         .to_string();
 
-    let commands: Vec<Commands>;
-    match project::session_exists(project_name) {
+    let server = Server::from(&args).or(project::server(&yaml[0]));
+    let attach_mode = AttachMode::new(args.flag_nested.as_deref(), tmux::inside_tmux(&server))?;
+
+    let mut commands: Vec<Commands>;
+    match project::session_exists(&server, project_name, attach_mode) {
         Some(c) => {
             commands = vec![c];
         }
         None => {
-            let config = Config::from_string(tmux::get_config());
-            commands = parser::call(&yaml, project_name, args.flag_d, &config)
-                .expect("Couldn't parse commands");
+            let config = Config::from_string(tmux::get_config(&server));
+            commands = parser::call(
+                &yaml,
+                project_name,
+                args.flag_d,
+                args.flag_with_contents,
+                attach_mode,
+                &config,
+            )?;
+
+            if let Some(ref branch) = args.flag_worktree {
+                worktree::tag(&mut commands, project_name, &args.arg_project, branch);
+            };
+
+            restart::hook(&mut commands, project_name, &server)?;
         }
     };
 
@@ -41,12 +79,64 @@ pub fn exec(args: Args) -> Result<(), String> {
         println!("{:?}", &commands);
     };
 
+    if let Some(version) = tmux::version(&server) {
+        for command in &commands {
+            for feature in command.as_trait().features() {
+                feature.check(&version)?;
+            }
+        }
+    };
+
+    // The client attaches last, and only returns once it's detached. The
+    // launch is kept in the history before then, once the project is up.
+    let attach = match commands.last() {
+        Some(Commands::Attach(_)) => commands.pop().filter(|_| attach),
+        _ => None,
+    };
+
+    // Windows and panes are targeted by the ids tmux gives them. Each command
+    // is pointed at the ids recorded from the output of the commands before it.
+    let mut ids = Ids::default();
+
     for command in &commands {
-        command
-            .as_trait()
-            .call(args.flag_debug)
-            .map_err(|e| format!("Had a problem running commands for tmux {}", e))?;
+        run(command, &server, &mut ids, args.flag_debug)?;
     }
 
+    // Opening goes on without the history when it can't be written.
+    let root = parser::expand_path(&yaml[0]["root"]).map(|r| r.to_path_buf());
+    let _ = history::record(&project_paths, &Launch::new(&args, root));
+
+    if let Some(attach) = attach {
+        attach
+            .as_trait()
+            .call(&server, args.flag_debug)
+            .map_err(|e| format!("Had a problem running commands for tmux: {}", e))?;
+    };
+
+    Ok((project_name.to_string(), server))
+}
+
+/// Run a command against the ids recorded so far, then the commands it holds
+/// back. A pane that gave up waiting isn't started, the rest of the project
+/// still opens.
+fn run(command: &Commands, server: &Server, ids: &mut Ids, debug: bool) -> Result<(), String> {
+    let command = command.resolve(ids);
+    let output = match command.as_trait().call(server, debug) {
+        Ok(output) => output,
+        Err(ref e) if e.kind() == io::ErrorKind::TimedOut => {
+            println!("{}", e);
+            return Ok(());
+        }
+        Err(e) => return Err(format!("Had a problem running commands for tmux: {}", e)),
+    };
+
+    command.record(&output, ids);
+
+    if let Commands::WaitFor(ref wait) = command {
+        for command in &wait.then {
+            run(command, server, ids, debug)?;
+        }
+    };
+
     Ok(())
 }
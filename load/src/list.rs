@@ -0,0 +1,180 @@
+//! Muxed list. Every project, with the sessions running for it grouped under
+//! it, the ones opened most recently first. Sessions opened on a worktree are
+//! shown with their branch.
+//!
+//! ```console
+//! $ muxed list
+//! my_project
+//!   my_project
+//!   my_project-feature/login (feature/login)
+//! notes
+//! ```
+use common::args::Args;
+use common::project_paths::{project_paths, CONFIG_EXTENSION};
+use common::tmux::Server;
+use history;
+use project::worktree::{BRANCH_VAR, PROJECT_VAR};
+use std::fs;
+use std::path::Path;
+use tmux;
+
+/// A running session, and the project and branch it was opened with.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Running {
+    pub name: String,
+    pub project: Option<String>,
+    pub branch: Option<String>,
+}
+
+impl Running {
+    /// Whether the session was opened for the project.
+    pub fn belongs_to(&self, project: &str) -> bool {
+        match self.project {
+            Some(ref p) => p == project,
+            None => self.name == project,
+        }
+    }
+}
+
+pub fn exec(args: Args) -> Result<(), String> {
+    let project_paths = project_paths(&args);
+    let server = Server::from(&args);
+
+    let mut projects = projects(&project_paths.project_directory)?;
+    history::sort_by_recency(&mut projects, &history::launches(&project_paths)?);
+    let sessions = running(&server);
+
+    print!("{}", render(&projects, &sessions));
+    Ok(())
+}
+
+/// The names of the project files in the directory, sorted.
+pub fn projects(dir: &Path) -> Result<Vec<String>, String> {
+    let entries = fs::read_dir(dir)
+        .map_err(|e| format!("Could not read the project directory {}. Error: {}", dir.display(), e))?;
+
+    let mut projects: Vec<String> = entries
+        .filter_map(|e| e.ok())
+        .map(|e| e.path())
+        .filter(|p| p.extension().and_then(|e| e.to_str()) == Some(CONFIG_EXTENSION))
+        .filter_map(|p| p.file_stem().map(|s| s.to_string_lossy().to_string()))
+        .collect();
+
+    projects.sort();
+    Ok(projects)
+}
+
+/// The running sessions, with the projects they were opened for.
+pub fn running(server: &Server) -> Vec<Running> {
+    sessions(server)
+        .into_iter()
+        .map(|name| Running {
+            project: environment(server, &name, PROJECT_VAR),
+            branch: environment(server, &name, BRANCH_VAR),
+            name,
+        })
+        .collect()
+}
+
+/// The running sessions. There are none when the server isn't running.
+fn sessions(server: &Server) -> Vec<String> {
+    match tmux::call(server, &["list-sessions", "-F", "#{session_name}"]) {
+        Ok(ref output) if output.status.success() => String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .map(|l| l.to_string())
+            .collect(),
+        _ => vec![],
+    }
+}
+
+/// A variable from the session's environment.
+fn environment(server: &Server, session: &str, name: &str) -> Option<String> {
+    let output = tmux::call(server, &["show-environment", "-t", session, name]).ok()?;
+
+    if !output.status.success() {
+        return None;
+    };
+
+    let line = String::from_utf8_lossy(&output.stdout).trim_end().to_string();
+    let prefix = format!("{}=", name);
+
+    if line.starts_with(&prefix) {
+        Some(line.trim_start_matches(prefix.as_str()).to_string())
+    } else {
+        None
+    }
+}
+
+/// Projects with their sessions indented under them, and the sessions of no
+/// project after.
+fn render(projects: &[String], sessions: &[Running]) -> String {
+    let line = |session: &Running| match session.branch {
+        Some(ref branch) => format!("  {} ({})\n", session.name, branch),
+        None => format!("  {}\n", session.name),
+    };
+
+    let mut out = String::new();
+
+    for project in projects {
+        out.push_str(&format!("{}\n", project));
+        for session in sessions.iter().filter(|s| s.belongs_to(project)) {
+            out.push_str(&line(session));
+        }
+    }
+
+    let others: Vec<&Running> = sessions
+        .iter()
+        .filter(|s| !projects.iter().any(|p| s.belongs_to(p)))
+        .collect();
+
+    if !others.is_empty() {
+        out.push_str("Other sessions\n");
+        for session in others {
+            out.push_str(&line(session));
+        }
+    };
+
+    out
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn running(name: &str, project: Option<&str>, branch: Option<&str>) -> Running {
+        Running {
+            name: name.to_string(),
+            project: project.map(|p| p.to_string()),
+            branch: branch.map(|b| b.to_string()),
+        }
+    }
+
+    #[test]
+    fn expect_worktree_sessions_under_their_project() {
+        let projects = vec!["api".to_string(), "notes".to_string()];
+        let sessions = vec![
+            running("api-gateway", None, None),
+            running("api", None, None),
+            running("api-login", Some("api"), Some("login")),
+        ];
+
+        assert_eq!(
+            render(&projects, &sessions),
+            "api\n  api\n  api-login (login)\nnotes\nOther sessions\n  api-gateway\n"
+        )
+    }
+
+    #[test]
+    fn expect_only_project_files() {
+        let dir = common::rand_names::project_path();
+        let _ = fs::create_dir_all(dir.join("autosave"));
+        let _ = fs::write(dir.join("web.yml"), "");
+        let _ = fs::write(dir.join("api.yml"), "");
+        let _ = fs::write(dir.join("notes.txt"), "");
+
+        let found = projects(&dir);
+        let _ = fs::remove_dir_all(&dir);
+
+        assert_eq!(found, Ok(vec!["api".to_string(), "web".to_string()]))
+    }
+}
@@ -0,0 +1,366 @@
+//! Muxed pick. Choose the project to open from a list of them, narrowed down
+//! as you type. Letters match in order, not necessarily next to each other, so
+//! `mapi` finds `my_api`. Projects are listed by when they were last opened,
+//! newest first. Projects with a running session are marked, as are the ones
+//! opened most recently.
+//!
+//! Up and down, or ctrl-p and ctrl-n, move through the list. Enter opens the
+//! project, escape or ctrl-c leaves without opening anything.
+use common::args::Args;
+use common::project_paths::project_paths;
+use common::tmux::Server;
+use history::{self, Launch};
+use list::{self, Running};
+use std::io::{self, Read, Write};
+use std::os::unix::io::AsRawFd;
+use terminal::{window_size, RawMode};
+
+/// How many of the most recently opened projects are marked.
+static RECENT: usize = 3;
+
+/// What's shown before the typed filter.
+static PROMPT: &str = "Open a project: ";
+
+/// A project in the list.
+/// `name`: The project file's name.
+/// `running`: Whether a session is running for it.
+/// `recent`: Whether it's one of the projects opened most recently.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Item {
+    pub name: String,
+    pub running: bool,
+    pub recent: bool,
+}
+
+/// A key pressed in the picker.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Key {
+    Char(char),
+    Backspace,
+    Up,
+    Down,
+    Enter,
+    Cancel,
+}
+
+/// How picking ended.
+#[derive(Debug, Clone, PartialEq)]
+enum Picked {
+    Project(String),
+    Nothing,
+}
+
+/// The list as it's shown.
+/// `query`: What's been typed to filter the list.
+/// `selected`: The highlighted line of the filtered list.
+struct Picker {
+    items: Vec<Item>,
+    query: String,
+    selected: usize,
+}
+
+impl Picker {
+    fn new(items: Vec<Item>) -> Picker {
+        Picker {
+            items,
+            query: String::new(),
+            selected: 0,
+        }
+    }
+
+    /// The items matching the query, closest first.
+    fn matches(&self) -> Vec<&Item> {
+        let mut scored: Vec<(usize, &Item)> = self
+            .items
+            .iter()
+            .filter_map(|item| score(&self.query, &item.name).map(|s| (s, item)))
+            .collect();
+
+        scored.sort_by_key(|(s, _)| *s);
+        scored.into_iter().map(|(_, item)| item).collect()
+    }
+
+    /// Act on a key, returning how picking ended when it has.
+    fn press(&mut self, key: Key) -> Option<Picked> {
+        match key {
+            Key::Char(c) => {
+                self.query.push(c);
+                self.selected = 0;
+            }
+            Key::Backspace => {
+                self.query.pop();
+                self.selected = 0;
+            }
+            Key::Up => self.selected = self.selected.saturating_sub(1),
+            Key::Down => self.selected = (self.selected + 1).min(self.matches().len().saturating_sub(1)),
+            Key::Enter => {
+                return self
+                    .matches()
+                    .get(self.selected)
+                    .map(|item| Picked::Project(item.name.clone()))
+            }
+            Key::Cancel => return Some(Picked::Nothing),
+        };
+
+        None
+    }
+
+    /// Draw the prompt and as much of the list as fits in `rows`, scrolled to
+    /// keep the selected line in view.
+    fn render(&self, rows: usize) -> String {
+        let matches = self.matches();
+        let visible = rows.saturating_sub(1).max(1);
+        let offset = self.selected.saturating_sub(visible - 1);
+        let width = matches.iter().map(|item| item.name.chars().count()).max().unwrap_or(0);
+
+        let mut out = format!("\x1b[H\x1b[2J{}{}", PROMPT, self.query);
+
+        for (i, item) in matches.iter().enumerate().skip(offset).take(visible) {
+            let marks: Vec<&str> = vec![(item.running, "running"), (item.recent, "recent")]
+                .into_iter()
+                .filter(|(on, _)| *on)
+                .map(|(_, mark)| mark)
+                .collect();
+            let line = format!("{:width$}  {}", item.name, marks.join(", "), width = width);
+
+            if i == self.selected {
+                out.push_str(&format!("\r\n\x1b[7m> {}\x1b[0m", line.trim_end()));
+            } else {
+                out.push_str(&format!("\r\n  {}", line.trim_end()));
+            };
+        }
+
+        let column = PROMPT.len() + self.query.chars().count() + 1;
+        out.push_str(&format!("\x1b[1;{}H", column));
+        out
+    }
+}
+
+pub fn exec(args: Args) -> Result<(), String> {
+    let project_paths = project_paths(&args);
+    let mut projects = list::projects(&project_paths.project_directory)?;
+
+    if projects.is_empty() {
+        return Err(format!(
+            "There are no projects in {} to pick from. Make one with `muxed new <project>`.",
+            project_paths.project_directory.display()
+        ));
+    };
+
+    let launches = history::launches(&project_paths)?;
+    history::sort_by_recency(&mut projects, &launches);
+
+    let server = Server::from(&args);
+    let picker = Picker::new(items(&projects, &list::running(&server), &launches));
+
+    match pick(picker)? {
+        Picked::Project(name) => ::exec(Args {
+            arg_project: name,
+            ..args
+        }),
+        Picked::Nothing => Ok(()),
+    }
+}
+
+/// Show the picker on the alternate screen until something is picked.
+fn pick(mut picker: Picker) -> Result<Picked, String> {
+    let stdin = io::stdin();
+    let fd = stdin.as_raw_fd();
+    let raw_mode = RawMode::enter(fd).ok_or_else(|| "Picking a project needs a terminal.".to_string())?;
+    let mut stdout = io::stdout();
+    let mut buf = [0; 64];
+
+    let picked = (|| -> io::Result<Picked> {
+        write!(stdout, "\x1b[?1049h")?;
+
+        loop {
+            let rows = window_size(fd).map_or(24, |(_, rows)| rows as usize);
+            write!(stdout, "{}", picker.render(rows))?;
+            stdout.flush()?;
+
+            let read = stdin.lock().read(&mut buf)?;
+            if read == 0 {
+                return Ok(Picked::Nothing);
+            };
+
+            for key in keys(&buf[..read]) {
+                if let Some(picked) = picker.press(key) {
+                    return Ok(picked);
+                };
+            }
+        }
+    })();
+
+    let _ = write!(stdout, "\x1b[?1049l");
+    let _ = stdout.flush();
+    drop(raw_mode);
+
+    picked.map_err(|e| format!("We couldn't read the keys pressed: {}", e))
+}
+
+/// The projects as they're listed, marked when they're running or one of the
+/// `RECENT` opened last.
+fn items(projects: &[String], sessions: &[Running], launches: &[Launch]) -> Vec<Item> {
+    let recent: Vec<&String> = history::latest(launches)
+        .into_iter()
+        .map(|launch| &launch.project)
+        .filter(|project| projects.contains(project))
+        .take(RECENT)
+        .collect();
+
+    projects
+        .iter()
+        .map(|project| Item {
+            name: project.clone(),
+            running: sessions.iter().any(|s| s.belongs_to(project)),
+            recent: recent.contains(&project),
+        })
+        .collect()
+}
+
+/// How closely `name` matches `query`: the query's letters have to be in it,
+/// in order, ignoring case. Lower is closer, counting the letters skipped
+/// before the last match. None when it doesn't match.
+fn score(query: &str, name: &str) -> Option<usize> {
+    let mut skipped = 0;
+    let mut letters = name.chars().flat_map(char::to_lowercase);
+
+    for wanted in query.chars().flat_map(char::to_lowercase) {
+        loop {
+            match letters.next() {
+                Some(letter) if letter == wanted => break,
+                Some(_) => skipped += 1,
+                None => return None,
+            }
+        }
+    }
+
+    Some(skipped)
+}
+
+/// The keys in what was read from the terminal. An escape on its own is
+/// taken as the escape key, and other escape sequences are skipped.
+fn keys(bytes: &[u8]) -> Vec<Key> {
+    let text = String::from_utf8_lossy(bytes);
+    let mut chars = text.chars().peekable();
+    let mut keys = vec![];
+
+    while let Some(c) = chars.next() {
+        let key = match c {
+            '\x1b' => match (chars.next(), chars.next()) {
+                (None, _) => Some(Key::Cancel),
+                (Some('['), Some('A')) | (Some('O'), Some('A')) => Some(Key::Up),
+                (Some('['), Some('B')) | (Some('O'), Some('B')) => Some(Key::Down),
+                _ => None,
+            },
+            '\r' | '\n' => Some(Key::Enter),
+            '\x7f' | '\x08' => Some(Key::Backspace),
+            '\x10' => Some(Key::Up),
+            '\x0e' => Some(Key::Down),
+            '\x03' | '\x04' => Some(Key::Cancel),
+            c if !c.is_control() => Some(Key::Char(c)),
+            _ => None,
+        };
+
+        keys.extend(key);
+    }
+
+    keys
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn item(name: &str) -> Item {
+        Item {
+            name: name.to_string(),
+            running: false,
+            recent: false,
+        }
+    }
+
+    fn names(picker: &Picker) -> Vec<&str> {
+        picker.matches().iter().map(|item| item.name.as_str()).collect()
+    }
+
+    #[test]
+    fn expect_letters_in_order_to_match() {
+        assert_eq!(score("mapi", "my_api"), Some(2));
+        assert_eq!(score("API", "api-gateway"), Some(0));
+        assert_eq!(score("ipa", "my_api"), None)
+    }
+
+    #[test]
+    fn expect_closest_matches_first() {
+        let mut picker = Picker::new(vec![item("rapid"), item("notes"), item("api")]);
+        for c in "api".chars() {
+            picker.press(Key::Char(c));
+        }
+
+        assert_eq!(names(&picker), vec!["api", "rapid"])
+    }
+
+    #[test]
+    fn expect_enter_to_pick_the_selected_match() {
+        let mut picker = Picker::new(vec![item("api"), item("notes"), item("web")]);
+        picker.press(Key::Down);
+        picker.press(Key::Down);
+        picker.press(Key::Down);
+        picker.press(Key::Up);
+
+        assert_eq!(picker.press(Key::Enter), Some(Picked::Project("notes".to_string())));
+        picker.press(Key::Char('z'));
+        assert_eq!(picker.press(Key::Enter), None);
+        assert_eq!(picker.press(Key::Cancel), Some(Picked::Nothing))
+    }
+
+    #[test]
+    fn expect_keys_from_terminal_input() {
+        assert_eq!(
+            keys(b"a\x1b[B\x1b[A\x7f\r"),
+            vec![Key::Char('a'), Key::Down, Key::Up, Key::Backspace, Key::Enter]
+        );
+        assert_eq!(keys(b"\x1b"), vec![Key::Cancel]);
+        assert_eq!(keys(b"\x1b[C\x03"), vec![Key::Cancel])
+    }
+
+    #[test]
+    fn expect_running_and_recent_projects_marked() {
+        let projects: Vec<String> = ["api", "notes", "web", "docs", "blog"].iter().map(|p| p.to_string()).collect();
+        let sessions = vec![Running {
+            name: "web".to_string(),
+            project: None,
+            branch: None,
+        }];
+        let launch = |time: u64, project: &str| Launch {
+            time,
+            project: project.to_string(),
+            root: None,
+            options: vec![],
+        };
+        let launches = vec![launch(1, "api"), launch(2, "gone"), launch(3, "docs"), launch(4, "web"), launch(5, "api")];
+
+        let items = items(&projects, &sessions, &launches);
+        let recent: Vec<&str> = items.iter().filter(|i| i.recent).map(|i| i.name.as_str()).collect();
+
+        assert_eq!(recent, vec!["api", "web", "docs"]);
+        assert!(items[2].running && !items[1].running)
+    }
+
+    #[test]
+    fn expect_selected_line_highlighted() {
+        let picker = Picker::new(vec![
+            Item {
+                name: "api".to_string(),
+                running: true,
+                recent: true,
+            },
+            item("notes"),
+        ]);
+        let screen = picker.render(10);
+
+        assert!(screen.contains("\r\n\x1b[7m> api    running, recent\x1b[0m"));
+        assert!(screen.contains("\r\n  notes\x1b[1;17H"))
+    }
+}
@@ -0,0 +1,88 @@
+//! The `env_file` setting. Variables from `.env` files the session and every
+//! pane in it start with, instead of sourcing the file in each pane.
+//!
+//! ```yaml
+//! env_file: .env
+//! ```
+//!
+//! or, with later files overriding the ones before
+//!
+//! ```yaml
+//! env_file:
+//!   - .env
+//!   - .env.local
+//! ```
+use common::procfile::{parse_env, Variable};
+use project::parser::resolve_path;
+use std::fs;
+use std::path::PathBuf;
+use std::rc::Rc;
+use yaml_rust::Yaml;
+
+/// The variables of every env file in the setting. Paths are relative to
+/// `root`.
+pub fn read(setting: &Yaml, root: &Option<Rc<PathBuf>>) -> Result<Vec<Variable>, String> {
+    let files = match *setting {
+        Yaml::Array(ref files) => files.iter().collect(),
+        Yaml::BadValue | Yaml::Null => vec![],
+        _ => vec![setting],
+    };
+
+    let mut env: Vec<Variable> = vec![];
+
+    for file in files {
+        let file = resolve_path(file, root)
+            .ok_or_else(|| "The env_file setting should be a path or a list of paths.".to_string())?;
+        let text = fs::read_to_string(file.as_ref())
+            .map_err(|e| format!("Could not read the env file {}. Error: {}", file.display(), e))?;
+
+        for (name, value) in parse_env(&text) {
+            match env.iter_mut().find(|(n, _)| *n == name) {
+                Some(variable) => variable.1 = value,
+                None => env.push((name, value)),
+            };
+        }
+    }
+
+    Ok(env)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use common::rand_names;
+    use yaml_rust::YamlLoader;
+
+    fn setting(text: &str) -> Yaml {
+        YamlLoader::load_from_str(text).unwrap().remove(0)
+    }
+
+    #[test]
+    fn expect_later_files_to_override() {
+        let dir = rand_names::project_path();
+        let _ = fs::create_dir_all(&dir);
+        let _ = fs::write(dir.join(".env"), "PORT=3000\nexport RACK_ENV=development\n");
+        let _ = fs::write(dir.join(".env.local"), "# mine\nPORT='4000'\n");
+
+        let env = read(&setting("[.env, .env.local]"), &Some(Rc::new(dir.clone())));
+        let _ = fs::remove_dir_all(&dir);
+
+        assert_eq!(
+            env,
+            Ok(vec![
+                ("PORT".to_string(), "4000".to_string()),
+                ("RACK_ENV".to_string(), "development".to_string()),
+            ])
+        )
+    }
+
+    #[test]
+    fn expect_nothing_without_the_setting() {
+        assert_eq!(read(&Yaml::BadValue, &None), Ok(vec![]))
+    }
+
+    #[test]
+    fn expect_err_when_a_file_is_missing() {
+        assert!(read(&setting("/tmp/muxed-no-such-dir/.env"), &None).is_err())
+    }
+}
@@ -0,0 +1,454 @@
+//! Windows repeated for every item of a list. The list is the paths matching
+//! a glob, the lines a command prints, or the members of a Cargo or npm
+//! workspace. `{{ item }}` in the window is replaced with the item, and
+//! `{{ item.name }}` with its name. For paths that's the package name from
+//! its manifest, or its last component.
+//!
+//! ```yaml
+//! windows:
+//!   - for_each: packages/*
+//!     window:
+//!       "{{ item.name }}":
+//!         path: "{{ item }}"
+//!         panes: ["npm test -- --watch"]
+//! ```
+//!
+//! or with `for_each:` set to one of
+//!
+//! ```yaml
+//! glob: services/*/
+//! command: git ls-files '*/Makefile' | xargs -n1 dirname
+//! workspace: Cargo.toml
+//! workspace: package.json
+//! ```
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::rc::Rc;
+use yaml_rust::{Yaml, YamlLoader};
+
+#[derive(Debug, PartialEq)]
+pub struct Item {
+    pub value: String,
+    pub name: String,
+}
+
+impl Item {
+    /// An item named after its last path component.
+    fn from_path(value: String) -> Item {
+        let name = Path::new(&value)
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_else(|| value.clone());
+
+        Item { value, name }
+    }
+}
+
+/// Replace every `for_each` in the windows with its windows. Paths are
+/// relative to `root`, and so are the paths given to the window.
+pub fn expand(windows: &[Yaml], root: &Option<Rc<PathBuf>>) -> Result<Vec<Yaml>, String> {
+    let base = root.as_ref().map_or_else(|| PathBuf::from("."), |r| r.to_path_buf());
+    let mut expanded = vec![];
+
+    for window in windows {
+        if window["for_each"].is_badvalue() {
+            expanded.push(window.clone());
+            continue;
+        };
+
+        let template = &window["window"];
+        if template.is_badvalue() {
+            return Err("A for_each needs a `window` to repeat.".into());
+        };
+
+        for item in items(&window["for_each"], &base)? {
+            expanded.push(substitute(template, &item));
+        }
+    }
+
+    if expanded.is_empty() {
+        return Err("No windows were left once for_each was expanded.".into());
+    };
+
+    Ok(expanded)
+}
+
+fn items(source: &Yaml, base: &Path) -> Result<Vec<Item>, String> {
+    if let Some(pattern) = source.as_str().or_else(|| source["glob"].as_str()) {
+        return Ok(relative(glob(base, pattern), base).into_iter().map(Item::from_path).collect());
+    };
+
+    if let Some(command) = source["command"].as_str() {
+        return command_items(command, base);
+    };
+
+    if let Some(manifest) = source["workspace"].as_str() {
+        return workspace_items(&base.join(manifest), base);
+    };
+
+    Err("for_each takes a glob, or a `glob`, `command` or `workspace` setting.".into())
+}
+
+/// An item for every line a command prints. The command runs in `base`.
+fn command_items(command: &str, base: &Path) -> Result<Vec<Item>, String> {
+    let output = Command::new("sh")
+        .args(["-c", command])
+        .current_dir(base)
+        .output()
+        .map_err(|e| format!("The for_each command `{}` couldn't run: {}", command, e))?;
+
+    if !output.status.success() {
+        return Err(format!(
+            "The for_each command `{}` failed: {}",
+            command,
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    };
+
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .map(|l| l.trim())
+        .filter(|l| !l.is_empty())
+        .map(|l| Item::from_path(l.to_string()))
+        .collect())
+}
+
+/// An item for every member of the workspace, named after the package.
+fn workspace_items(manifest: &Path, base: &Path) -> Result<Vec<Item>, String> {
+    let text = fs::read_to_string(manifest)
+        .map_err(|e| format!("Could not read the workspace {}. Error: {}", manifest.display(), e))?;
+    let dir = manifest.parent().unwrap_or(base);
+    let npm = manifest.extension().and_then(|e| e.to_str()) == Some("json");
+
+    let patterns = if npm { npm_workspaces(&text)? } else { cargo_members(&text) };
+    let mut members: Vec<PathBuf> = patterns.iter().flat_map(|p| glob(dir, p)).collect();
+    members.sort();
+    members.dedup();
+
+    Ok(members
+        .iter()
+        .map(|member| {
+            let value = relative(vec![dir.join(member)], base).remove(0);
+            let name = if npm { npm_name(&dir.join(member)) } else { cargo_name(&dir.join(member)) };
+
+            match name {
+                Some(name) => Item { value, name },
+                None => Item::from_path(value),
+            }
+        })
+        .collect())
+}
+
+/// The `members` of the `[workspace]` table of a Cargo.toml.
+fn cargo_members(text: &str) -> Vec<String> {
+    let mut members = vec![];
+    let mut table = String::new();
+    let mut in_members = false;
+
+    for line in text.lines() {
+        let line = line.split('#').next().unwrap_or("").trim();
+
+        if !in_members && line.starts_with('[') {
+            table = line.to_string();
+            continue;
+        };
+
+        let value = if in_members {
+            line
+        } else if table == "[workspace]" && line.starts_with("members") {
+            in_members = true;
+            &line[line.find('=').map_or(line.len(), |i| i + 1)..]
+        } else {
+            continue;
+        };
+
+        // Strings are every other piece between quotes.
+        members.extend(value.split('"').skip(1).step_by(2).map(|s| s.to_string()));
+
+        if value.contains(']') {
+            in_members = false;
+        };
+    }
+
+    members
+}
+
+/// The `name` of the `[package]` table of a crate.
+fn cargo_name(dir: &Path) -> Option<String> {
+    let text = fs::read_to_string(dir.join("Cargo.toml")).ok()?;
+    let mut table = "";
+
+    for line in text.lines().map(|l| l.trim()) {
+        if line.starts_with('[') {
+            table = line;
+        } else if table == "[package]" && line.starts_with("name") {
+            return line.split('"').nth(1).map(|s| s.to_string());
+        };
+    }
+
+    None
+}
+
+/// The `workspaces` of a package.json, as a list or under `packages`.
+fn npm_workspaces(text: &str) -> Result<Vec<String>, String> {
+    let doc = YamlLoader::load_from_str(text).map_err(|e| format!("The package.json isn't valid: {}", e))?;
+    let workspaces = &doc[0]["workspaces"];
+    let list = workspaces.as_vec().or_else(|| workspaces["packages"].as_vec());
+
+    Ok(list
+        .into_iter()
+        .flatten()
+        .filter_map(|w| w.as_str().map(|s| s.to_string()))
+        .collect())
+}
+
+fn npm_name(dir: &Path) -> Option<String> {
+    let text = fs::read_to_string(dir.join("package.json")).ok()?;
+    let doc = YamlLoader::load_from_str(&text).ok()?;
+    doc.first()?["name"].as_str().map(|s| s.to_string())
+}
+
+/// Paths matching a glob, sorted. `*` and `?` match within a single path
+/// component, and hidden entries only match patterns starting with a `.`.
+pub fn glob(base: &Path, pattern: &str) -> Vec<PathBuf> {
+    let start = if pattern.starts_with('/') { PathBuf::from("/") } else { base.to_path_buf() };
+    let mut paths = vec![start];
+
+    for component in pattern.split('/').filter(|c| !c.is_empty() && *c != ".") {
+        let mut next = vec![];
+
+        for path in &paths {
+            if !component.contains(&['*', '?'][..]) {
+                let candidate = path.join(component);
+                if candidate.exists() {
+                    next.push(candidate);
+                };
+                continue;
+            };
+
+            let entries = match fs::read_dir(path) {
+                Ok(entries) => entries,
+                Err(_) => continue,
+            };
+
+            for entry in entries.filter_map(|e| e.ok()) {
+                let name = entry.file_name().to_string_lossy().to_string();
+
+                if (component.starts_with('.') || !name.starts_with('.')) && wildcard(component, &name) {
+                    next.push(path.join(name));
+                };
+            }
+        }
+
+        paths = next;
+    }
+
+    paths.sort();
+    paths
+}
+
+/// Whether a name matches a pattern of `*` and `?` wildcards.
+fn wildcard(pattern: &str, name: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let name: Vec<char> = name.chars().collect();
+    // matched[j] is whether the pattern so far matches the first j characters.
+    let mut matched = vec![false; name.len() + 1];
+    matched[0] = true;
+
+    for p in &pattern {
+        let mut next = vec![false; name.len() + 1];
+
+        for j in 0..=name.len() {
+            next[j] = match p {
+                '*' => matched[j] || (j > 0 && next[j - 1]),
+                '?' => j > 0 && matched[j - 1],
+                c => j > 0 && matched[j - 1] && name[j - 1] == *c,
+            };
+        }
+
+        matched = next;
+    }
+
+    matched[name.len()]
+}
+
+/// Paths relative to `base` when they're below it.
+fn relative(paths: Vec<PathBuf>, base: &Path) -> Vec<String> {
+    paths
+        .iter()
+        .map(|p| p.strip_prefix(base).unwrap_or(p).display().to_string())
+        .collect()
+}
+
+/// The window with the item filled in to every string, names included.
+fn substitute(yaml: &Yaml, item: &Item) -> Yaml {
+    match *yaml {
+        Yaml::String(ref s) => Yaml::String(fill(s, item)),
+        Yaml::Array(ref a) => Yaml::Array(a.iter().map(|y| substitute(y, item)).collect()),
+        Yaml::Hash(ref h) => {
+            Yaml::Hash(h.iter().map(|(k, v)| (substitute(k, item), substitute(v, item))).collect())
+        }
+        _ => yaml.clone(),
+    }
+}
+
+/// Replace `{{ item }}` and `{{ item.name }}`. Anything else in braces is
+/// left as written.
+fn fill(text: &str, item: &Item) -> String {
+    let mut filled = String::new();
+    let mut rest = text;
+
+    while let Some(start) = rest.find("{{") {
+        let end = match rest[start..].find("}}") {
+            Some(end) => start + end,
+            None => break,
+        };
+
+        filled.push_str(&rest[..start]);
+        match rest[start + 2..end].trim() {
+            "item" => filled.push_str(&item.value),
+            "item.name" => filled.push_str(&item.name),
+            _ => filled.push_str(&rest[start..end + 2]),
+        };
+        rest = &rest[end + 2..];
+    }
+
+    filled.push_str(rest);
+    filled
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use common::rand_names;
+
+    fn item(value: &str, name: &str) -> Item {
+        Item {
+            value: value.to_string(),
+            name: name.to_string(),
+        }
+    }
+
+    fn tree(files: &[(&str, &str)]) -> PathBuf {
+        let dir = rand_names::project_path();
+        for (file, contents) in files {
+            let path = dir.join(file);
+            let _ = fs::create_dir_all(path.parent().unwrap());
+            let _ = fs::write(path, contents);
+        }
+        dir
+    }
+
+    #[test]
+    fn expect_wildcards_to_match() {
+        assert!(wildcard("*", "api"));
+        assert!(wildcard("a?i", "api"));
+        assert!(wildcard("*-service", "billing-service"));
+        assert!(!wildcard("*-service", "billing-worker"));
+        assert!(!wildcard("a?", "api"))
+    }
+
+    #[test]
+    fn expect_glob_to_match_sorted_and_skip_hidden() {
+        let dir = tree(&[
+            ("packages/web/package.json", "{}"),
+            ("packages/api/package.json", "{}"),
+            ("packages/.cache/x", ""),
+        ]);
+        let paths = relative(glob(&dir, "packages/*"), &dir);
+        let _ = fs::remove_dir_all(&dir);
+
+        assert_eq!(paths, vec!["packages/api", "packages/web"])
+    }
+
+    #[test]
+    fn expect_cargo_members_on_one_line_or_many() {
+        let text = "[package]\nname = \"root\"\n\n[workspace]\nmembers = [\n    \"common\", # shared\n    \"crates/*\",\n]\n";
+        assert_eq!(cargo_members(text), vec!["common", "crates/*"]);
+        assert_eq!(cargo_members("[workspace]\nmembers = [\"a\", \"b\"]\n"), vec!["a", "b"])
+    }
+
+    #[test]
+    fn expect_npm_workspaces_in_both_forms() {
+        assert_eq!(npm_workspaces("{\"workspaces\": [\"packages/*\"]}"), Ok(vec!["packages/*".to_string()]));
+        assert_eq!(
+            npm_workspaces("{\"workspaces\": {\"packages\": [\"apps/*\"]}}"),
+            Ok(vec!["apps/*".to_string()])
+        )
+    }
+
+    #[test]
+    fn expect_cargo_workspace_members_named_after_their_package() {
+        let dir = tree(&[
+            ("Cargo.toml", "[workspace]\nmembers = [\"crates/*\"]\n"),
+            ("crates/core/Cargo.toml", "[package]\nname = \"muxed-core\"\n"),
+            ("crates/docs/README.md", ""),
+        ]);
+        let items = items(&YamlLoader::load_from_str("workspace: Cargo.toml").unwrap()[0], &dir);
+        let _ = fs::remove_dir_all(&dir);
+
+        assert_eq!(
+            items,
+            Ok(vec![item("crates/core", "muxed-core"), item("crates/docs", "docs")])
+        )
+    }
+
+    #[test]
+    fn expect_npm_workspace_members_named_after_their_package() {
+        let dir = tree(&[
+            ("package.json", "{\"workspaces\": [\"packages/*\"]}"),
+            ("packages/ui/package.json", "{\"name\": \"@acme/ui\"}"),
+        ]);
+        let items = items(&YamlLoader::load_from_str("workspace: package.json").unwrap()[0], &dir);
+        let _ = fs::remove_dir_all(&dir);
+
+        assert_eq!(items, Ok(vec![item("packages/ui", "@acme/ui")]))
+    }
+
+    #[test]
+    fn expect_an_item_per_line_of_output() {
+        let items = command_items("printf 'services/api\\n\\nservices/web\\n'", Path::new("/tmp"));
+        assert_eq!(
+            items,
+            Ok(vec![item("services/api", "api"), item("services/web", "web")])
+        )
+    }
+
+    #[test]
+    fn expect_item_to_be_filled_in() {
+        let item = item("packages/api", "api");
+        assert_eq!(fill("{{ item.name }}: cd {{item}} && {{ other }}", &item), "api: cd packages/api && {{ other }}")
+    }
+
+    #[test]
+    fn expect_a_window_per_item_in_place() {
+        let dir = tree(&[("packages/api/x", ""), ("packages/web/x", "")]);
+        let yaml = YamlLoader::load_from_str(
+            "
+- editor: vim
+- for_each: packages/*
+  window:
+    '{{ item.name }}':
+      path: '{{ item }}'
+      panes: ['npm test']
+- logs: ''
+",
+        )
+        .unwrap();
+        let windows = expand(yaml[0].as_vec().unwrap(), &Some(Rc::new(dir.clone())));
+        let _ = fs::remove_dir_all(&dir);
+        let windows = windows.unwrap();
+
+        assert_eq!(windows.len(), 4);
+        assert_eq!(windows[1]["api"]["path"].as_str(), Some("packages/api"));
+        assert_eq!(windows[2]["web"]["panes"][0].as_str(), Some("npm test"));
+        assert!(!windows[3]["logs"].is_badvalue())
+    }
+
+    #[test]
+    fn expect_err_without_a_window() {
+        let yaml = YamlLoader::load_from_str("- for_each: '*'").unwrap();
+        assert!(expand(yaml[0].as_vec().unwrap(), &None).is_err())
+    }
+}
@@ -0,0 +1,196 @@
+//! The `log` settings. Every pane's output is appended to a file of its own,
+//! named after the session, window and pane. They can be set for the session,
+//! a window or a pane, the closest one winning.
+//!
+//! ```yaml
+//! log: true
+//! log_dir: ./tmp/muxed-logs
+//! log_timestamps: true
+//! log_strip_ansi: true
+//! windows:
+//!   - editor:
+//!       log: false
+//!       panes: ['vim']
+//! ```
+use common::shell::shell_quote;
+use project::parser::resolve_path;
+use std::env;
+use std::path::PathBuf;
+use std::rc::Rc;
+use yaml_rust::Yaml;
+
+/// Where logs go when only `log: true` is set, relative to the root.
+static DEFAULT_DIR: &str = "tmp/muxed-logs";
+
+/// Drops colours, cursor movement and carriage returns from the output. Lines
+/// are flushed as they come, to be followed with `tail -f`.
+static STRIP_ANSI: &str = "awk '{ gsub(/\\033\\[[0-9;?]*[A-Za-z]|\\r/, \"\"); print; fflush() }'";
+
+/// Puts the time in front of every line.
+static TIMESTAMP: &str =
+    "while IFS= read -r line; do printf '%s %s\\n' \"$(date '+%Y-%m-%d %H:%M:%S')\" \"$line\"; done";
+
+/// How a pane is logged.
+/// `dir`: The directory the log files are written to.
+/// `timestamps`: Whether every line starts with the time it was written.
+/// `strip_ansi`: Whether escape sequences are dropped from the output.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Log {
+    pub dir: PathBuf,
+    pub timestamps: bool,
+    pub strip_ansi: bool,
+}
+
+impl Log {
+    /// The shell command `pipe-pane` writes the pane's output to.
+    pub fn command(&self, session: &str, window: &str, pane: usize) -> String {
+        let file = self.dir.join(file_name(session, window, pane));
+        let mut stages = vec![];
+
+        if self.strip_ansi {
+            stages.push(STRIP_ANSI);
+        };
+
+        if self.timestamps {
+            stages.push(TIMESTAMP);
+        };
+
+        if stages.is_empty() {
+            stages.push("cat");
+        };
+
+        format!(
+            "mkdir -p {} && {} >> {}",
+            shell_quote(&self.dir.to_string_lossy()),
+            stages.join(" | "),
+            shell_quote(&file.to_string_lossy())
+        )
+    }
+}
+
+/// The log settings of a pane. `levels` are the session, window and pane
+/// settings, from the outside in. None when the pane isn't logged.
+pub fn settings(levels: &[&Yaml], root: &Option<Rc<PathBuf>>) -> Option<Log> {
+    let mut enabled = false;
+    let mut dir = None;
+    let mut timestamps = false;
+    let mut strip_ansi = false;
+
+    for level in levels {
+        if let Some(d) = resolve_path(&level["log_dir"], root) {
+            enabled = true;
+            dir = Some(d);
+        };
+
+        if let Some(on) = level["log"].as_bool() {
+            enabled = on;
+        };
+
+        if let Some(on) = level["log_timestamps"].as_bool() {
+            timestamps = on;
+        };
+
+        if let Some(on) = level["log_strip_ansi"].as_bool() {
+            strip_ansi = on;
+        };
+    }
+
+    if !enabled {
+        return None;
+    };
+
+    // pipe-pane doesn't run in the pane's directory, so the path is made whole.
+    let dir = match (dir, root) {
+        (Some(dir), _) => dir.to_path_buf(),
+        (None, Some(root)) => root.join(DEFAULT_DIR),
+        (None, None) => PathBuf::from(DEFAULT_DIR),
+    };
+    let dir = match env::current_dir() {
+        Ok(ref current) if dir.is_relative() => current.join(dir),
+        _ => dir,
+    };
+
+    Some(Log {
+        dir,
+        timestamps,
+        strip_ansi,
+    })
+}
+
+/// `<session>-<window>-<pane>.log`, with anything that doesn't belong in a
+/// file name replaced.
+fn file_name(session: &str, window: &str, pane: usize) -> String {
+    let name = format!("{}-{}-{}", session, window, pane);
+    let safe = |c: char| c.is_ascii_alphanumeric() || c == '-' || c == '_' || c == '.';
+
+    format!("{}.log", name.chars().map(|c| if safe(c) { c } else { '_' }).collect::<String>())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use yaml_rust::YamlLoader;
+
+    fn yaml(text: &str) -> Yaml {
+        YamlLoader::load_from_str(text).unwrap().remove(0)
+    }
+
+    #[test]
+    fn expect_the_closest_setting_to_win() {
+        let root = Some(Rc::new(PathBuf::from("/src/app")));
+        let session = yaml("{ log: true, log_timestamps: true }");
+        let window = yaml("{ log_dir: logs }");
+        let pane = yaml("{ log: false }");
+
+        assert_eq!(
+            settings(&[&session, &window], &root),
+            Some(Log {
+                dir: PathBuf::from("/src/app/logs"),
+                timestamps: true,
+                strip_ansi: false,
+            })
+        );
+        assert_eq!(settings(&[&session, &window, &pane], &root), None)
+    }
+
+    #[test]
+    fn expect_default_dir_under_the_root() {
+        let root = Some(Rc::new(PathBuf::from("/src/app")));
+        let log = settings(&[&yaml("log: true")], &root).unwrap();
+
+        assert_eq!(log.dir, PathBuf::from("/src/app/tmp/muxed-logs"))
+    }
+
+    #[test]
+    fn expect_no_log_by_default() {
+        assert_eq!(settings(&[&yaml("windows: []"), &yaml("vim")], &None), None)
+    }
+
+    #[test]
+    fn expect_plain_append_to_a_file_per_pane() {
+        let log = Log {
+            dir: PathBuf::from("/tmp/my logs"),
+            timestamps: false,
+            strip_ansi: false,
+        };
+
+        assert_eq!(
+            log.command("app", "rails server", 1),
+            "mkdir -p '/tmp/my logs' && cat >> '/tmp/my logs/app-rails_server-1.log'"
+        )
+    }
+
+    #[test]
+    fn expect_ansi_stripped_before_timestamps() {
+        let log = Log {
+            dir: PathBuf::from("/tmp"),
+            timestamps: true,
+            strip_ansi: true,
+        };
+        let command = log.command("app", "web", 0);
+
+        assert!(command.starts_with("mkdir -p /tmp && awk "));
+        assert!(command.contains(" | while IFS= read -r line;"));
+        assert!(command.ends_with("done >> /tmp/app-web-0.log"))
+    }
+}
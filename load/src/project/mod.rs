@@ -1,10 +1,18 @@
 //! The project module takes care of muxed related initialization. Locating the
 //! users home directory. Finding the desired config files, and reading the
 //! configs in.
+pub mod env_file;
+pub mod for_each;
+pub mod log;
 pub mod parser;
+pub mod procfile;
+pub mod supervise;
+pub mod wait_for;
+pub mod worktree;
 
-use command::{Attach, Commands};
+use command::{Attach, AttachMode, Commands};
 use common::project_paths::ProjectPaths;
+use common::tmux::Server;
 use first_run::check_first_run;
 use std::fs::File;
 use std::io::prelude::*;
@@ -36,7 +44,11 @@ pub fn read(project_name: &str, project_paths: &ProjectPaths) -> Result<Vec<Yaml
     file.read_to_string(&mut contents)
         .map_err(|e| e.to_string())?;
 
-    let parsed_yaml = YamlLoader::load_from_str(&contents).map_err(|e| e.to_string())?;
+    let mut parsed_yaml = YamlLoader::load_from_str(&contents).map_err(|e| e.to_string())?;
+
+    if let Some(doc) = parsed_yaml.first_mut() {
+        procfile::expand(doc)?;
+    };
 
     Ok(parsed_yaml)
 }
@@ -44,14 +56,71 @@ pub fn read(project_name: &str, project_paths: &ProjectPaths) -> Result<Vec<Yaml
 /// Find out if a tmux session is already active with this name. If it is active
 /// return `Some<Commands::Attach>` with a command to attach to the session. If a
 /// session is not active return None and let the app carry on.
-pub fn session_exists(project_name: &str) -> Option<Commands> {
-    if has_session(project_name).success() {
-        Some(Attach::new(&project_name, None).into())
+pub fn session_exists<'a>(
+    server: &Server,
+    project_name: &'a str,
+    attach_mode: AttachMode,
+) -> Option<Commands<'a>> {
+    if has_session(server, project_name).success() {
+        Some(Attach::new(&project_name, None, attach_mode).into())
     } else {
         None
     }
 }
 
+/// Read the tmux server settings from the project file. These are the
+/// `tmux_command`, `socket_name`, `socket_path` and `tmux_config` keys.
+///
+/// # Examples
+///
+/// ```
+/// tmux_command: wemux
+/// socket_name: client-engagement
+/// ```
+pub fn server(doc: &Yaml) -> Server {
+    let setting = |key: &str| doc[key].as_str().map(|s| s.to_string());
+
+    Server::new(
+        setting("tmux_command"),
+        setting("socket_name"),
+        setting("socket_path"),
+        setting("tmux_config"),
+    )
+}
+
+/// The windows a project opens, named, with their settings, in the order
+/// they're opened. Windows written as just a name, or a name and a command,
+/// have no settings. `for_each` windows are expanded.
+pub fn windows(doc: &Yaml) -> Result<Vec<(String, Yaml)>, String> {
+    let root = parser::expand_path(&doc["root"]);
+    let windows = doc["windows"]
+        .as_vec()
+        .ok_or_else(|| "No Windows have been defined.".to_string())?;
+
+    named(&for_each::expand(windows, &root)?)
+}
+
+/// Name windows that have already been expanded, with their settings.
+pub fn named(windows: &[Yaml]) -> Result<Vec<(String, Yaml)>, String> {
+    let mut named = vec![];
+    for window in windows {
+        match window {
+            Yaml::Hash(h) => {
+                for (k, v) in h {
+                    let name = k.as_str().ok_or("Windows require being named in your config.")?;
+                    let settings = if v.as_hash().is_some() { v.clone() } else { Yaml::BadValue };
+                    named.push((name.to_string(), settings));
+                }
+            }
+            Yaml::String(s) => named.push((s.clone(), Yaml::BadValue)),
+            Yaml::Integer(i) => named.push((i.to_string(), Yaml::BadValue)),
+            _ => return Err("Muxed config file formatting isn't recognized.".into()),
+        };
+    }
+
+    Ok(named)
+}
+
 #[cfg(test)]
 mod test {
     use common::rand_names;
@@ -98,4 +167,45 @@ mod test {
         let _ = fs::remove_file(&project_paths.project_file);
         assert!(result.is_ok());
     }
+
+    #[test]
+    fn server_settings_are_read() {
+        let yaml = YamlLoader::load_from_str(
+            "---
+    tmux_command: wemux
+    socket_name: client
+    tmux_config: ~/.tmux.dev.conf
+    windows: ['cargo']
+    ",
+        )
+        .unwrap();
+        let server = server(&yaml[0]);
+        let config = dirs::home_dir().unwrap().join(".tmux.dev.conf");
+
+        assert_eq!(server.command, Some("wemux".to_string()));
+        assert_eq!(server.socket_name, Some("client".to_string()));
+        assert_eq!(server.socket_path, None);
+        assert_eq!(server.config_file, Some(config.display().to_string()))
+    }
+
+    #[test]
+    fn windows_are_named_in_order() {
+        let yaml = YamlLoader::load_from_str(
+            "---
+    windows:
+      - editor:
+          panes: ['vim', 'ls']
+      - logs: tail -f log/development.log
+      - git
+      - 3
+    ",
+        )
+        .unwrap();
+        let windows = windows(&yaml[0]).unwrap();
+        let names: Vec<&str> = windows.iter().map(|(name, _)| name.as_str()).collect();
+
+        assert_eq!(names, vec!["editor", "logs", "git", "3"]);
+        assert_eq!(windows[0].1["panes"][1].as_str(), Some("ls"));
+        assert!(windows[1].1.is_badvalue())
+    }
 }
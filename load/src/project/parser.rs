@@ -2,8 +2,9 @@
 //! processed later.
 
 use command::*;
-use dirs::home_dir;
-use std::path::{Path, PathBuf};
+use common::project_paths::expand_home;
+use project::{self, env_file, for_each, log, supervise, wait_for};
+use std::path::PathBuf;
 use std::rc::Rc;
 use tmux::config::Config;
 use tmux::target::*;
@@ -15,10 +16,15 @@ use yaml_rust::Yaml;
 ///
 /// `yaml_string`: The parsed yaml from the config file.
 /// `project_name`: The name of the project.
+/// `daemonize`: Skip attaching to the session once it's built.
+/// `contents`: Replay the pane contents saved by a snapshot.
+/// `attach_mode`: How to attach to the session when not daemonized.
 pub fn call<'a>(
     yaml_string: &'a [Yaml],
     project_name: &'a str,
     daemonize: bool,
+    contents: bool,
+    attach_mode: AttachMode,
     tmux_config: &Config,
 ) -> Result<Vec<Commands<'a>>, String> {
     let mut commands: Vec<Commands> = vec![];
@@ -27,21 +33,39 @@ pub fn call<'a>(
     let doc = &yaml_string[0];
 
     let root = expand_path(&doc["root"]);
-    let pre_window = pre_matcher(&doc["pre_window"]);
+    let pre_window: Vec<String> = pre_matcher(&doc["pre_window"])
+        .unwrap_or_default()
+        .into_iter()
+        .flatten()
+        .collect();
+
+    // Every window starts with the variables of the `env_file`.
+    let env = env_file::read(&doc["env_file"], &root)?;
+    let window_env: Rc<Vec<String>> = Rc::new(env.iter().map(|(k, v)| format!("{}={}", k, v)).collect());
 
     // A clojure used to capture the current local root and pre Options.
     // This way we can call the clojure to create common SendKeys command
     // like changing the directory or executing a system command from the
-    // `pre_window` option.
-    let common_commands = |target: Target| -> Vec<Commands> {
+    // `pre_window` option. `settings` are the window's and pane's settings,
+    // for the ones they can override. `typed_pre` is false for panes that run
+    // `pre_window` as part of their command instead.
+    let common_commands = |target: Target, settings: &[&Yaml], typed_pre: bool| -> Vec<Commands> {
         let mut commands2 = vec![];
 
+        // Log the output from the start, before anything runs.
+        let levels: Vec<&Yaml> = Some(doc).into_iter().chain(settings.iter().cloned()).collect();
+        if let Some(log) = log::settings(&levels, &root) {
+            let exec = match target {
+                Target::PaneTarget(ref p) => log.command(&p.session, &p.window, p.pane_index),
+                Target::WindowTarget(ref w) => log.command(&w.session, &w.window, tmux_config.pane_base_index),
+            };
+            commands2.push(PipePane::new(target.clone(), exec).into());
+        };
+
         // SendKeys for the Pre option
-        if let Some(p) = pre_window.clone() {
-            for v in &p {
-                if let Some(ref r) = *v {
-                    commands2.push(SendKeys::new(target.clone(), r.clone()).into());
-                };
+        if typed_pre {
+            for p in &pre_window {
+                commands2.push(SendKeys::new(target.clone(), p.clone()).into());
             }
         };
 
@@ -51,37 +75,60 @@ pub fn call<'a>(
     let windows = doc["windows"]
         .as_vec()
         .expect("No Windows have been defined.");
+    let windows = for_each::expand(windows, &root)?;
+
+    // The commands of panes waiting on a `wait_for`, run once every window
+    // has been created.
+    let mut waiting: Vec<Commands> = vec![];
+
+    // Windows are counted as they're created. The count is their position in
+    // the project, used to target them by id once tmux has created them.
+    let mut position = 0;
+
+    // The window marked `active`, and for every window whether one of its
+    // panes was marked `active`.
+    let mut focus: Option<usize> = None;
+    let mut focused_panes: Vec<bool> = vec![];
 
     for window in windows.iter() {
         match *window {
             Yaml::Hash(ref h) => {
                 for (k, v) in h {
                     if v.as_hash().is_some() {
-                        let path = match expand_path(&v["path"]) {
-                            Some(x) => Some(x),
-                            None => root.clone(),
-                        };
+                        let window_path = resolve_path(&v["path"], &root).or_else(|| root.clone());
+                        // The window opens in its first pane's directory.
+                        let path =
+                            resolve_path(&v["panes"][0]["path"], &root).or_else(|| window_path.clone());
 
                         commands.push(
                             Window::new(
                                 &project_name,
                                 Rc::new(k.as_str().expect("window should have a name").to_string()),
-                                path.clone(),
+                                path,
                             )
+                            .with_env(Rc::clone(&window_env))
                             .into(),
                         );
 
                         let target = WindowTarget::new(
                             project_name,
                             k.as_str().ok_or_else(|| "no target specified")?,
-                        );
-                        commands.append(&mut pane_matcher(
-                            v,
-                            &target,
-                            &common_commands,
-                            &tmux_config,
-                            path.clone(),
-                        )?);
+                        )
+                        .at(position);
+                        let inherited = Inherited {
+                            root: &root,
+                            path: window_path,
+                            pre_window: &pre_window,
+                        };
+                        let (mut panes, mut panes_waiting) =
+                            pane_matcher(v, &target, common_commands, inherited, tmux_config, contents)?;
+                        commands.append(&mut panes);
+                        waiting.append(&mut panes_waiting);
+
+                        if v["active"].as_bool() == Some(true) {
+                            focus = Some(position);
+                        };
+                        focused_panes.push(focused_pane(v).is_some());
                     } else {
                         commands.push(Window::new(
                             &project_name,
@@ -90,10 +137,10 @@ pub fn call<'a>(
                             })?
                             .to_string()),
                             root.clone()
-                        ).into());
+                        ).with_env(Rc::clone(&window_env)).into());
 
-                        let target = WindowTarget::new(project_name, k.as_str().unwrap());
-                        commands.append(&mut common_commands(Target::WindowTarget(target.clone())));
+                        let target = WindowTarget::new(project_name, k.as_str().unwrap()).at(position);
+                        commands.append(&mut common_commands(Target::WindowTarget(target.clone()), &[], true));
 
                         // SendKeys for the exec command
                         if let Some(ex) = v.as_str() {
@@ -107,23 +154,36 @@ pub fn call<'a>(
                                 );
                             };
                         }
+
+                        focused_panes.push(false);
                     }
+
+                    position += 1;
                 }
             }
             Yaml::String(ref s) => {
-                commands
-                    .push(Window::new(&project_name, Rc::new(s.to_string()), root.clone()).into());
+                commands.push(
+                    Window::new(&project_name, Rc::new(s.to_string()), root.clone())
+                        .with_env(Rc::clone(&window_env))
+                        .into(),
+                );
 
-                let target = WindowTarget::new(&project_name, &s);
-                commands.append(&mut common_commands(Target::WindowTarget(target)));
+                let target = WindowTarget::new(&project_name, &s).at(position);
+                commands.append(&mut common_commands(Target::WindowTarget(target), &[], true));
+                focused_panes.push(false);
+                position += 1;
             }
             Yaml::Integer(ref s) => {
                 commands.push(
-                    Window::new(&project_name, Rc::new(format!("{}", s)), root.clone()).into(),
+                    Window::new(&project_name, Rc::new(format!("{}", s)), root.clone())
+                        .with_env(Rc::clone(&window_env))
+                        .into(),
                 );
 
-                let target = WindowTarget::new(&project_name, &s.to_string());
-                commands.append(&mut common_commands(Target::WindowTarget(target)));
+                let target = WindowTarget::new(&project_name, &s.to_string()).at(position);
+                commands.append(&mut common_commands(Target::WindowTarget(target), &[], true));
+                focused_panes.push(false);
+                position += 1;
             }
             _ => panic!("Muxed config file formatting isn't recognized."),
         };
@@ -135,27 +195,46 @@ pub fn call<'a>(
     if let Commands::Window(ref w) = &first {
         remains.insert(
             0,
-            Session::new(&project_name, Rc::clone(&w.name), root.clone()).into(),
+            Session::new(&project_name, Rc::clone(&w.name), root.clone())
+                .with_env(Rc::clone(&w.env))
+                .into(),
         );
 
+        // Set on the session as well, for the panes split off later.
+        for (i, (name, value)) in env.iter().enumerate() {
+            remains.insert(1 + i, SetEnvironment::new(project_name, name.clone(), value.clone()).into());
+        }
+
         if let Some(path) = &w.path {
             remains.insert(
                 1,
                 SendKeys::new(
-                    Target::WindowTarget(WindowTarget::new(&project_name, &w.name)),
+                    Target::WindowTarget(WindowTarget::new(&project_name, &w.name).at(0)),
                     format!("cd {}", path.display()),
                 )
                 .into(),
             );
         }
+    };
 
-        remains.push(SelectWindow::new(WindowTarget::new(&project_name, &w.name)).into());
+    // Focus the window marked `active`, or the first one. Unless one of its
+    // panes was marked, focus the top pane.
+    let focus = focus.unwrap_or(0);
+    let name = commands
+        .iter()
+        .filter_map(|c| match c {
+            Commands::Window(w) => Some(Rc::clone(&w.name)),
+            _ => None,
+        })
+        .nth(focus)
+        .ok_or_else(|| "The focused window couldn't be found.".to_string())?;
+
+    remains.push(SelectWindow::new(WindowTarget::new(&project_name, &name).at(focus)).into());
+    if !focused_panes[focus] {
         remains.push(
-            SelectPane::new(PaneTarget::new(
-                &project_name,
-                &w.name,
-                tmux_config.base_index,
-            ))
+            SelectPane::new(
+                PaneTarget::new(&project_name, &name, tmux_config.pane_base_index).at(focus, 0),
+            )
             .into(),
         );
     };
@@ -171,56 +250,122 @@ pub fn call<'a>(
         }
     };
 
+    // Panes named in a `wait_for` are found once every window is known.
+    if !waiting.is_empty() {
+        let named = project::named(&windows)?;
+
+        for command in waiting.iter_mut() {
+            if let Commands::WaitFor(ref mut w) = command {
+                w.condition = w.condition.clone().resolve(project_name, &named, tmux_config)?;
+            };
+        }
+    };
+
+    remains.append(&mut waiting);
+
     if !daemonize {
-        remains.push(Attach::new(&project_name, root).into());
+        remains.push(Attach::new(&project_name, root, attach_mode).into());
     };
 
     Ok(remains)
 }
 
+/// What a window's panes inherit from the project and the window.
+/// `root`: The project root. Relative pane paths are resolved against it.
+/// `path`: The window's directory, for panes without a path of their own.
+/// `pre_window`: Run in every pane ahead of its command.
+struct Inherited<'b> {
+    root: &'b Option<Rc<PathBuf>>,
+    path: Option<Rc<PathBuf>>,
+    pre_window: &'b [String],
+}
+
 /// Pane matcher is for breaking apart the panes. Splitting windows when needed
-/// and executing commands as needed.
+/// and executing commands as needed. The commands of panes with a `wait_for`
+/// are returned apart, to run once the session is up.
 fn pane_matcher<'a, T>(
-    window: &'a Yaml,
+    window: &Yaml,
     target: &WindowTarget,
     common_commands: T,
+    inherited: Inherited,
     tmux_config: &Config,
-    inherited_path: Option<Rc<PathBuf>>,
-) -> Result<Vec<Commands<'a>>, String>
+    contents: bool,
+) -> Result<(Vec<Commands<'a>>, Vec<Commands<'a>>), String>
 where
-    T: Fn(Target) -> Vec<Commands<'a>>,
+    T: Fn(Target, &[&Yaml], bool) -> Vec<Commands<'a>>,
 {
+    let Inherited { root, path, pre_window } = inherited;
     let mut commands = vec![];
+    let mut waiting = vec![];
     let panes = window["panes"]
         .as_vec()
         .expect("Something is wrong with panes.");
 
-    let path = match expand_path(&window["path"]) {
-        Some(x) => Some(x),
-        None => inherited_path,
-    };
-
     for (i, pane) in panes.iter().enumerate() {
-        let pt = PaneTarget::new(
+        let mut pt = PaneTarget::new(
             &target.session,
             &target.window,
             i + tmux_config.pane_base_index,
         );
+
+        if let Some(window) = target.position {
+            pt = pt.at(window, i);
+        };
         // For every pane, we need one less split.
-        // ex. An existing window to become 2 panes, needs 1 split.
+        // ex. An existing window to become 2 panes, needs 1 split. The split
+        // opens the next pane, so it takes the next pane's path.
         if i < (panes.len() - 1) {
-            commands.push(Split::new(pt.clone(), path.clone()).into());
+            let next_path = resolve_path(&panes[i + 1]["path"], root).or_else(|| path.clone());
+            commands.push(Split::new(pt.clone(), next_path).into());
+        };
+
+        // Replay what the pane showed before anything else runs in it.
+        if let (true, Some(file)) = (contents, expand_path(&pane["contents"])) {
+            let pane_path = resolve_path(&pane["path"], root).or_else(|| path.clone());
+            commands.push(Contents::new(pt.clone(), pane_path, &file).into());
         };
 
+        // A command to keep running replaces the pane's shell, so its exits
+        // can be seen. Anything typed in to the shell would be lost with it.
+        let policy = supervise::parse(pane, window)?;
+
         // Call the common_commands clojure to execute `cd` and `pre_window` options in
-        // pane splits.
-        commands.append(&mut common_commands(Target::PaneTarget(pt.clone())));
+        // pane splits, and start its log.
+        commands.append(&mut common_commands(
+            Target::PaneTarget(pt.clone()),
+            &[window, pane],
+            policy.is_none(),
+        ));
+
+        // A pane waiting on something is started once the rest of the session
+        // is up, instead of holding back the panes after it.
+        let wait = match pane["wait_for"] {
+            Yaml::BadValue => wait_for::parse(&window["wait_for"])?,
+            ref setting => wait_for::parse(setting)?,
+        };
 
         // Execute given commands in each new pane after all splits are
         // complete.
-        if let Some(p) = pane.as_str() {
+        if let Some(p) = pane.as_str().or_else(|| pane["command"].as_str()) {
             if !p.is_empty() {
-                commands.push(SendKeys::new(Target::PaneTarget(pt.clone()), p.to_string()).into());
+                let pane_path = resolve_path(&pane["path"], root).or_else(|| path.clone());
+
+                // `pre_window` runs ahead of a supervised command, and again
+                // with every restart.
+                let start: Commands = match policy {
+                    Some(ref policy) => {
+                        let exec = supervise::with_pre(pre_window, p);
+                        Supervise::new(pt.clone(), exec, pane_path.clone(), policy).into()
+                    }
+                    None => SendKeys::new(Target::PaneTarget(pt.clone()), p.to_string()).into(),
+                };
+
+                match wait {
+                    Some((condition, timeout)) => {
+                        waiting.push(WaitFor::new(pt.clone(), condition, timeout, pane_path, vec![start]).into());
+                    }
+                    None => commands.push(start),
+                };
             };
         };
     }
@@ -235,7 +380,17 @@ where
         commands.push(Layout::new(target.clone(), layout.to_string()).into());
     };
 
-    Ok(commands)
+    // Panes are focused last, once the layout has settled.
+    if let Some(i) = focused_pane(window) {
+        let mut pt = PaneTarget::new(&target.session, &target.window, i + tmux_config.pane_base_index);
+
+        if let Some(window) = target.position {
+            pt = pt.at(window, i);
+        };
+        commands.push(SelectPane::new(pt).into());
+    };
+
+    Ok((commands, waiting))
 }
 
 fn pre_matcher(node: &Yaml) -> Option<Vec<Option<String>>> {
@@ -255,19 +410,31 @@ fn pre_matcher(node: &Yaml) -> Option<Vec<Option<String>>> {
     }
 }
 
-fn expand_path(node: &Yaml) -> Option<Rc<PathBuf>> {
-    match node.as_str() {
-        Some(string) => Some(if string.contains("~/") {
-            let home = home_dir().expect("Home dir could not be expanded");
-            let path = home.join(Path::new(string).strip_prefix("~/").unwrap());
-            Rc::new(path)
-        } else {
-            Rc::new(PathBuf::from(string))
-        }),
-        None => None,
+/// The index of the pane marked `active` in a window, if any.
+fn focused_pane(window: &Yaml) -> Option<usize> {
+    window["panes"]
+        .as_vec()?
+        .iter()
+        .position(|p| p["active"].as_bool() == Some(true))
+}
+
+/// Expand a window or pane path. Relative paths are relative to the project
+/// root. Paths starting with a variable, like `$HOME`, are left to tmux.
+pub fn resolve_path(node: &Yaml, root: &Option<Rc<PathBuf>>) -> Option<Rc<PathBuf>> {
+    let path = expand_path(node)?;
+
+    match root {
+        Some(root) if path.is_relative() && !path.to_string_lossy().starts_with('$') => {
+            Some(Rc::new(root.join(path.as_ref())))
+        }
+        _ => Some(path),
     }
 }
 
+pub fn expand_path(node: &Yaml) -> Option<Rc<PathBuf>> {
+    node.as_str().map(|string| Rc::new(expand_home(string)))
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -283,9 +450,12 @@ mod test {
             &yaml,
             "muxed",
             false,
+            false,
+            AttachMode::Attach,
             &Config {
                 base_index: 0,
                 pane_base_index: 0,
+                ..Default::default()
             },
         )
         .unwrap()
@@ -309,9 +479,12 @@ mod test {
             &yaml,
             "muxed",
             false,
+            false,
+            AttachMode::Attach,
             &Config {
                 base_index: 0,
                 pane_base_index: 0,
+                ..Default::default()
             },
         )
         .unwrap()
@@ -335,9 +508,12 @@ mod test {
             &yaml,
             "muxed",
             false,
+            false,
+            AttachMode::Attach,
             &Config {
                 base_index: 0,
                 pane_base_index: 0,
+                ..Default::default()
             },
         )
         .unwrap()
@@ -361,9 +537,12 @@ mod test {
             &yaml,
             "muxed",
             false,
+            false,
+            AttachMode::Attach,
             &Config {
                 base_index: 0,
                 pane_base_index: 0,
+                ..Default::default()
             },
         )
         .unwrap()
@@ -389,9 +568,12 @@ mod test {
             &yaml,
             "muxed",
             false,
+            false,
+            AttachMode::Attach,
             &Config {
                 base_index: 0,
                 pane_base_index: 0,
+                ..Default::default()
             },
         )
         .unwrap()
@@ -415,9 +597,12 @@ mod test {
             &yaml,
             "muxed",
             false,
+            false,
+            AttachMode::Attach,
             &Config {
                 base_index: 0,
                 pane_base_index: 0,
+                ..Default::default()
             },
         );
         assert!(result.is_ok())
@@ -435,9 +620,12 @@ mod test {
             &yaml,
             "muxed",
             false,
+            false,
+            AttachMode::Attach,
             &Config {
                 base_index: 0,
                 pane_base_index: 0,
+                ..Default::default()
             },
         )
         .unwrap()
@@ -462,9 +650,12 @@ mod test {
             &yaml,
             "muxed",
             false,
+            false,
+            AttachMode::Attach,
             &Config {
                 base_index: 0,
                 pane_base_index: 0,
+                ..Default::default()
             },
         );
         assert!(result.is_err())
@@ -484,9 +675,12 @@ mod test {
             &yaml,
             "muxed",
             false,
+            false,
+            AttachMode::Attach,
             &Config {
                 base_index: 0,
                 pane_base_index: 0,
+                ..Default::default()
             },
         );
         assert!(result.is_ok())
@@ -506,9 +700,12 @@ mod test {
             &yaml,
             "muxed",
             false,
+            false,
+            AttachMode::Attach,
             &Config {
                 base_index: 0,
                 pane_base_index: 0,
+                ..Default::default()
             },
         )
         .unwrap()
@@ -536,9 +733,12 @@ mod test {
             &yaml,
             "muxed",
             false,
+            false,
+            AttachMode::Attach,
             &Config {
                 base_index: 0,
                 pane_base_index: 0,
+                ..Default::default()
             },
         )
         .unwrap()
@@ -566,9 +766,12 @@ mod test {
             &yaml,
             "muxed",
             false,
+            false,
+            AttachMode::Attach,
             &Config {
                 base_index: 0,
                 pane_base_index: 0,
+                ..Default::default()
             },
         )
         .unwrap()
@@ -596,9 +799,12 @@ mod test {
             &yaml,
             "muxed",
             false,
+            false,
+            AttachMode::Attach,
             &Config {
                 base_index: 0,
                 pane_base_index: 0,
+                ..Default::default()
             },
         )
         .unwrap()
@@ -625,9 +831,12 @@ mod test {
             &yaml,
             "muxed",
             false,
+            false,
+            AttachMode::Attach,
             &Config {
                 base_index: 0,
                 pane_base_index: 0,
+                ..Default::default()
             },
         )
         .unwrap()
@@ -658,9 +867,12 @@ mod test {
             &yaml,
             "muxed",
             false,
+            false,
+            AttachMode::Attach,
             &Config {
                 base_index: 0,
                 pane_base_index: 0,
+                ..Default::default()
             },
         )
         .unwrap()
@@ -688,9 +900,12 @@ mod test {
             &yaml,
             "muxed",
             false,
+            false,
+            AttachMode::Attach,
             &Config {
                 base_index: 0,
                 pane_base_index: 0,
+                ..Default::default()
             },
         )
         .unwrap()
@@ -704,6 +919,53 @@ mod test {
         assert_eq!(remains.len(), 2)
     }
 
+    #[test]
+    pub fn expect_pre_window_in_the_command_of_supervised_panes() {
+        // The supervised pane's shell is replaced, so pre is only typed in
+        // to the other pane.
+        let s = "---
+    pre_window: 'source .env'
+    windows:
+      - server:
+          panes:
+            - command: rails s
+              restart: always
+            - vim
+    ";
+        let yaml = YamlLoader::load_from_str(s).unwrap();
+        let commands = call(
+            &yaml,
+            "muxed",
+            true,
+            false,
+            AttachMode::Attach,
+            &Config {
+                base_index: 0,
+                pane_base_index: 0,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+
+        let typed: Vec<String> = commands
+            .iter()
+            .filter_map(|c| match c {
+                Commands::SendKeys(k) => Some(k.exec.clone()),
+                _ => None,
+            })
+            .collect();
+        let supervised: Vec<String> = commands
+            .iter()
+            .filter_map(|c| match c {
+                Commands::Supervise(s) => Some(s.exec.clone()),
+                _ => None,
+            })
+            .collect();
+
+        assert_eq!(typed, vec!["source .env", "vim"]);
+        assert_eq!(supervised, vec!["source .env && {\nrails s\n}"])
+    }
+
     #[test]
     pub fn expect_no_send_keys_with_blank_panes() {
         let s = "---
@@ -717,9 +979,12 @@ mod test {
             &yaml,
             "muxed",
             false,
+            false,
+            AttachMode::Attach,
             &Config {
                 base_index: 0,
                 pane_base_index: 0,
+                ..Default::default()
             },
         )
         .unwrap()
@@ -745,9 +1010,12 @@ mod test {
             &yaml,
             "muxed",
             false,
+            false,
+            AttachMode::Attach,
             &Config {
                 base_index: 0,
                 pane_base_index: 0,
+                ..Default::default()
             },
         )
         .unwrap()
@@ -774,9 +1042,12 @@ mod test {
             &yaml,
             "financials",
             false,
+            false,
+            AttachMode::Attach,
             &Config {
                 base_index: 0,
                 pane_base_index: 0,
+                ..Default::default()
             },
         )
         .unwrap()
@@ -808,9 +1079,12 @@ mod test {
             &yaml,
             "muxed",
             false,
+            false,
+            AttachMode::Attach,
             &Config {
                 base_index: 0,
                 pane_base_index: 0,
+                ..Default::default()
             },
         )
         .unwrap()
@@ -834,9 +1108,12 @@ mod test {
             &yaml,
             "muxed",
             false,
+            false,
+            AttachMode::Attach,
             &Config {
                 base_index: 0,
                 pane_base_index: 0,
+                ..Default::default()
             },
         )
         .unwrap()
@@ -894,4 +1171,226 @@ mod test {
         let pre = pre_matcher(&yaml[0]["pre"]);
         assert!(pre.is_none())
     }
+
+    #[test]
+    pub fn expect_relative_pane_paths_to_join_root() {
+        let s = "---
+    root: /tmp/project
+    windows:
+      - editor:
+          path: src
+          panes:
+            - vim
+            - command: tail -f log
+              path: /var/log
+            - path: ../docs
+    ";
+        let yaml = YamlLoader::load_from_str(s).unwrap();
+        let paths: Vec<Option<Rc<PathBuf>>> = call(
+            &yaml,
+            "muxed",
+            false,
+            false,
+            AttachMode::Attach,
+            &Config::default(),
+        )
+        .unwrap()
+        .into_iter()
+        .filter_map(|x| match x {
+            Commands::Session(s) => Some(s.root_path),
+            Commands::Split(s) => Some(s.path),
+            _ => None,
+        })
+        .collect();
+
+        let expected = vec![
+            Some(Rc::new(PathBuf::from("/tmp/project"))),
+            Some(Rc::new(PathBuf::from("/var/log"))),
+            Some(Rc::new(PathBuf::from("/tmp/project/../docs"))),
+        ];
+        assert_eq!(paths, expected)
+    }
+
+    #[test]
+    pub fn expect_window_to_open_in_first_pane_path() {
+        let s = "---
+    root: /tmp/project
+    windows:
+      - editor: ''
+      - logs:
+          panes:
+            - path: log
+    ";
+        let yaml = YamlLoader::load_from_str(s).unwrap();
+        let window = call(&yaml, "muxed", false, false, AttachMode::Attach, &Config::default())
+            .unwrap()
+            .into_iter()
+            .find_map(|x| match x {
+                Commands::Window(w) => Some(w),
+                _ => None,
+            })
+            .unwrap();
+
+        assert_eq!(window.path, Some(Rc::new(PathBuf::from("/tmp/project/log"))))
+    }
+
+    #[test]
+    pub fn expect_focus_on_active_window_and_pane() {
+        let s = "---
+    windows:
+      - editor: ''
+      - logs:
+          active: true
+          panes:
+            - ''
+            - command: top
+              active: true
+    ";
+        let yaml = YamlLoader::load_from_str(s).unwrap();
+        let targets: Vec<String> = call(
+            &yaml,
+            "muxed",
+            false,
+            false,
+            AttachMode::Attach,
+            &Config::default(),
+        )
+        .unwrap()
+        .into_iter()
+        .filter_map(|x| match x {
+            Commands::SelectWindow(c) => Some(c.target.arg_string),
+            Commands::SelectPane(c) => Some(c.target.arg_string),
+            _ => None,
+        })
+        .collect();
+
+        assert_eq!(targets, vec!["muxed:logs.1", "muxed:logs"])
+    }
+
+    #[test]
+    pub fn expect_contents_to_be_replayed_only_when_asked() {
+        let s = "---
+    windows:
+      - editor:
+          panes:
+            - contents: /tmp/muxed.contents/0.0
+            - ''
+    ";
+        let yaml = YamlLoader::load_from_str(s).unwrap();
+        let count = |contents: bool| {
+            call(&yaml, "muxed", false, contents, AttachMode::Attach, &Config::default())
+                .unwrap()
+                .into_iter()
+                .filter(|x| match x {
+                    Commands::Contents(_) => true,
+                    _ => false,
+                })
+                .count()
+        };
+
+        assert_eq!((count(true), count(false)), (1, 0))
+    }
+
+    #[test]
+    pub fn expect_env_file_on_the_session_and_every_window() {
+        let dir = common::rand_names::project_path();
+        let _ = std::fs::create_dir_all(&dir);
+        let _ = std::fs::write(dir.join(".env"), "PORT=3000\n");
+
+        let s = format!("---\n    root: {}\n    env_file: .env\n    windows: ['vim', 'git']\n", dir.display());
+        let yaml = YamlLoader::load_from_str(&s).unwrap();
+        let commands = call(&yaml, "muxed", true, false, AttachMode::Attach, &Config::default());
+        let _ = std::fs::remove_dir_all(&dir);
+
+        let args: Vec<Vec<String>> = commands
+            .unwrap()
+            .iter()
+            .filter(|c| match c {
+                Commands::Session(_) | Commands::SetEnvironment(_) | Commands::Window(_) => true,
+                _ => false,
+            })
+            .map(|c| c.as_trait().args().iter().map(|a| a.to_string()).collect())
+            .collect();
+
+        assert!(args[0].ends_with(&["-e".to_string(), "PORT=3000".to_string()]));
+        assert_eq!(args[1], vec!["set-environment", "-t", "muxed", "PORT", "3000"]);
+        assert!(args[2].ends_with(&["-e".to_string(), "PORT=3000".to_string()]))
+    }
+
+    #[test]
+    pub fn expect_waiting_panes_to_start_after_the_session_is_up() {
+        let s = "---
+    windows:
+      - app:
+          wait_for: { port: 5432 }
+          panes:
+            - rails s
+            - command: tail -f log/development.log
+              wait_for: { file: log/development.log }
+      - db:
+          panes: ['postgres']
+    ";
+        let yaml = YamlLoader::load_from_str(s).unwrap();
+        let commands = call(&yaml, "muxed", true, false, AttachMode::Attach, &Config::default()).unwrap();
+        fn names(commands: &[Commands]) -> Vec<String> {
+            commands
+                .iter()
+                .flat_map(|c| match c {
+                    Commands::SendKeys(k) => vec![k.exec.clone()],
+                    Commands::WaitFor(w) => {
+                        let mut waited = vec![format!("wait for {}", w.condition)];
+                        waited.append(&mut names(&w.then));
+                        waited
+                    }
+                    Commands::SelectWindow(_) => vec!["select".to_string()],
+                    _ => vec![],
+                })
+                .collect()
+        }
+        let names = names(&commands);
+
+        assert_eq!(
+            names,
+            vec![
+                "postgres",
+                "select",
+                "wait for port 5432 on localhost",
+                "rails s",
+                "wait for the file log/development.log",
+                "tail -f log/development.log",
+            ]
+        )
+    }
+
+    #[test]
+    pub fn expect_a_log_pipe_for_every_pane_logged() {
+        let s = "---
+    root: /src/app
+    log: true
+    windows:
+      - server:
+          panes:
+            - rails s
+            - command: vim
+              log: false
+      - logs: 'tail -f log/development.log'
+    ";
+        let yaml = YamlLoader::load_from_str(s).unwrap();
+        let pipes: Vec<String> = call(&yaml, "app", true, false, AttachMode::Attach, &Config::default())
+            .unwrap()
+            .into_iter()
+            .filter_map(|c| match c {
+                Commands::PipePane(p) => Some(p.exec),
+                _ => None,
+            })
+            .collect();
+
+        assert_eq!(
+            pipes,
+            vec![
+                "mkdir -p /src/app/tmp/muxed-logs && cat >> /src/app/tmp/muxed-logs/app-server-0.log",
+                "mkdir -p /src/app/tmp/muxed-logs && cat >> /src/app/tmp/muxed-logs/app-logs-0.log",
+            ]
+        )
+    }
 }
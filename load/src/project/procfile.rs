@@ -0,0 +1,194 @@
+//! The `procfile` setting. Every process of a Procfile becomes a window, or a
+//! pane of one window when the window is named. Processes run in the
+//! Procfile's directory with the variables of the `.env` file next to it, the
+//! way foreman runs them.
+//!
+//! ```yaml
+//! procfile: ./Procfile
+//! ```
+//!
+//! or
+//!
+//! ```yaml
+//! procfile:
+//!   file: ./Procfile
+//!   window: services
+//!   env: ./.env.development
+//! ```
+use common::procfile::{self, Process, Variable, ENV_FILE};
+use project::parser::{expand_path, resolve_path};
+use std::path::Path;
+use std::rc::Rc;
+use yaml_rust::yaml::Hash;
+use yaml_rust::Yaml;
+
+/// The layout of the window holding every process.
+static GRID_LAYOUT: &str = "tiled";
+
+/// Add the windows of the project's Procfile to its windows. Paths are
+/// resolved relative to `root`.
+pub fn expand(doc: &mut Yaml) -> Result<(), String> {
+    let windows = match windows(doc)? {
+        Some(windows) => windows,
+        None => return Ok(()),
+    };
+
+    if let Yaml::Hash(ref mut settings) = *doc {
+        let key = Yaml::String("windows".to_string());
+
+        match settings.get_mut(&key) {
+            Some(Yaml::Array(ref mut existing)) => existing.extend(windows),
+            _ => {
+                settings.insert(key, Yaml::Array(windows));
+            }
+        };
+    };
+
+    Ok(())
+}
+
+fn windows(doc: &Yaml) -> Result<Option<Vec<Yaml>>, String> {
+    let setting = &doc["procfile"];
+    let file = if setting.as_hash().is_some() { &setting["file"] } else { setting };
+
+    if file.is_badvalue() {
+        return Ok(None);
+    };
+
+    let root = expand_path(&doc["root"]);
+    let file = resolve_path(file, &root)
+        .ok_or_else(|| "The procfile setting should be the path to a Procfile.".to_string())?;
+    let dir = file.parent().unwrap_or_else(|| Path::new(".")).to_path_buf();
+    let env_file = resolve_path(&setting["env"], &root).unwrap_or_else(|| Rc::new(dir.join(ENV_FILE)));
+
+    let processes = procfile::read(&file)?;
+    let env = procfile::read_env(&env_file)?;
+    let dir = dir.display().to_string();
+
+    let windows = match setting["window"].as_str() {
+        Some(name) => vec![grid(name, &dir, &processes, &env)],
+        None => processes.iter().map(|p| window(p, &dir, &env)).collect(),
+    };
+
+    Ok(Some(windows))
+}
+
+/// A window running a single process.
+fn window(process: &Process, dir: &str, env: &[Variable]) -> Yaml {
+    let command = Yaml::String(procfile::with_env(&process.command, env));
+    named(&process.name, entry(dir, None, vec![command]))
+}
+
+/// A window with a pane for every process.
+fn grid(name: &str, dir: &str, processes: &[Process], env: &[Variable]) -> Yaml {
+    let panes = processes
+        .iter()
+        .map(|p| Yaml::String(procfile::with_env(&p.command, env)))
+        .collect();
+
+    named(name, entry(dir, Some(GRID_LAYOUT), panes))
+}
+
+fn entry(dir: &str, layout: Option<&str>, panes: Vec<Yaml>) -> Yaml {
+    let mut window = Hash::new();
+    window.insert(Yaml::String("path".to_string()), Yaml::String(dir.to_string()));
+
+    if let Some(layout) = layout {
+        window.insert(Yaml::String("layout".to_string()), Yaml::String(layout.to_string()));
+    };
+
+    window.insert(Yaml::String("panes".to_string()), Yaml::Array(panes));
+    Yaml::Hash(window)
+}
+
+fn named(name: &str, window: Yaml) -> Yaml {
+    let mut named = Hash::new();
+    named.insert(Yaml::String(name.to_string()), window);
+    Yaml::Hash(named)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use common::rand_names;
+    use std::fs;
+    use yaml_rust::YamlLoader;
+
+    fn project(procfile: &str, env: Option<&str>, settings: &str) -> (Yaml, std::path::PathBuf) {
+        let dir = rand_names::project_path();
+        let _ = fs::create_dir_all(&dir);
+        let _ = fs::write(dir.join("Procfile"), procfile);
+        if let Some(env) = env {
+            let _ = fs::write(dir.join(".env"), env);
+        };
+
+        let text = format!("root: {}\n{}", dir.display(), settings);
+        let mut yaml = YamlLoader::load_from_str(&text).unwrap();
+        (yaml.remove(0), dir)
+    }
+
+    #[test]
+    fn expect_a_window_per_process_after_the_others() {
+        let (mut doc, dir) = project(
+            "web: rails s\nworker: sidekiq\n",
+            None,
+            "procfile: Procfile\nwindows:\n  - editor: vim\n",
+        );
+        let result = expand(&mut doc);
+        let _ = fs::remove_dir_all(&dir);
+        let windows = doc["windows"].as_vec().unwrap();
+
+        assert_eq!(result, Ok(()));
+        assert_eq!(windows.len(), 3);
+        assert_eq!(windows[1]["web"]["panes"][0].as_str(), Some("rails s"));
+        assert_eq!(windows[1]["web"]["path"].as_str().map(Path::new), Some(dir.as_path()));
+        assert_eq!(windows[2]["worker"]["panes"][0].as_str(), Some("sidekiq"))
+    }
+
+    #[test]
+    fn expect_a_pane_per_process_in_a_named_window() {
+        let (mut doc, dir) = project(
+            "web: rails s\nworker: sidekiq\n",
+            None,
+            "procfile:\n  file: Procfile\n  window: services\n",
+        );
+        let result = expand(&mut doc);
+        let _ = fs::remove_dir_all(&dir);
+        let window = &doc["windows"][0]["services"];
+
+        assert_eq!(result, Ok(()));
+        assert_eq!(window["layout"].as_str(), Some("tiled"));
+        assert_eq!(window["panes"][0].as_str(), Some("rails s"));
+        assert_eq!(window["panes"][1].as_str(), Some("sidekiq"))
+    }
+
+    #[test]
+    fn expect_env_to_be_exported_before_each_process() {
+        let (mut doc, dir) = project("web: rails s -p $PORT\n", Some("PORT=3000\n"), "procfile: Procfile\n");
+        let result = expand(&mut doc);
+        let _ = fs::remove_dir_all(&dir);
+
+        assert_eq!(result, Ok(()));
+        assert_eq!(
+            doc["windows"][0]["web"]["panes"][0].as_str(),
+            Some("export PORT=3000; rails s -p $PORT")
+        )
+    }
+
+    #[test]
+    fn expect_nothing_without_a_procfile_setting() {
+        let mut doc = YamlLoader::load_from_str("windows: ['vim']").unwrap().remove(0);
+        let before = doc.clone();
+
+        assert_eq!(expand(&mut doc), Ok(()));
+        assert_eq!(doc, before)
+    }
+
+    #[test]
+    fn expect_err_when_the_procfile_is_missing() {
+        let mut doc = YamlLoader::load_from_str("procfile: /tmp/muxed-no-such-dir/Procfile")
+            .unwrap()
+            .remove(0);
+        assert!(expand(&mut doc).is_err())
+    }
+}
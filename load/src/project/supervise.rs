@@ -0,0 +1,187 @@
+//! The `restart` setting. A pane's command runs in place of its shell and is
+//! started again when it exits, to keep dev servers up. The pane stays open
+//! when the command exits (`remain-on-exit`), and the session's `pane-died`
+//! hook calls `muxed restart` for it. The policy is kept in the pane's
+//! options for the restart to read.
+//!
+//! The shell the command replaces never gets `pre_window` typed in to it.
+//! It's run ahead of the command instead, which has to succeed for the
+//! command to start.
+//!
+//! ```yaml
+//! windows:
+//!   - server:
+//!       panes:
+//!         - command: rails s
+//!           restart: on-failure
+//!           restart_delay: 2s
+//!           max_restarts: 5
+//! ```
+use project::wait_for::duration;
+use std::time::Duration;
+use yaml_rust::Yaml;
+
+/// Pane options holding the policy. The delay is in milliseconds.
+pub static RESTART_OPTION: &str = "@muxed_restart";
+pub static DELAY_OPTION: &str = "@muxed_restart_delay";
+pub static MAX_OPTION: &str = "@muxed_max_restarts";
+pub static COUNT_OPTION: &str = "@muxed_restarts";
+
+/// How long to wait before restarting when no `restart_delay` is set.
+static DEFAULT_DELAY: Duration = Duration::from_secs(1);
+
+/// When to restart.
+/// `Always`: Whenever the command exits.
+/// `OnFailure`: When the command exits with a status other than 0.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Restart {
+    Always,
+    OnFailure,
+}
+
+impl Restart {
+    pub fn parse(text: &str) -> Option<Restart> {
+        match text {
+            "always" => Some(Restart::Always),
+            "on-failure" => Some(Restart::OnFailure),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Restart::Always => "always",
+            Restart::OnFailure => "on-failure",
+        }
+    }
+}
+
+/// A pane's restart settings.
+/// `restart`: When to restart.
+/// `delay`: How long to wait before restarting.
+/// `max_restarts`: How many times to restart before leaving the pane showing
+/// the last exit status. No limit when None.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Policy {
+    pub restart: Restart,
+    pub delay: Duration,
+    pub max_restarts: Option<u32>,
+}
+
+impl Policy {
+    /// Whether a command exiting with `status`, after being restarted
+    /// `restarts` times already, should be started again.
+    pub fn restarts(&self, status: i32, restarts: u32) -> bool {
+        let wanted = match self.restart {
+            Restart::Always => true,
+            Restart::OnFailure => status != 0,
+        };
+
+        wanted && self.max_restarts.map_or(true, |max| restarts < max)
+    }
+}
+
+/// The restart policy of a pane. The settings can be given on the pane, or
+/// on its window for every pane in it.
+pub fn parse(pane: &Yaml, window: &Yaml) -> Result<Option<Policy>, String> {
+    let setting = |key: &str| match pane[key] {
+        Yaml::BadValue => window[key].clone(),
+        ref value => value.clone(),
+    };
+
+    let restart = match setting("restart") {
+        Yaml::BadValue | Yaml::Boolean(false) => return Ok(None),
+        Yaml::String(ref s) if s == "no" => return Ok(None),
+        ref value => value.as_str().and_then(Restart::parse).ok_or_else(|| {
+            "The restart setting should be always, on-failure or no.".to_string()
+        })?,
+    };
+
+    let delay = match setting("restart_delay") {
+        Yaml::BadValue => DEFAULT_DELAY,
+        Yaml::Integer(secs) => Duration::from_secs(secs as u64),
+        ref value => value.as_str().and_then(duration).ok_or_else(|| {
+            "The restart_delay should be seconds, or a number ending in ms, s or m. ex. 2s".to_string()
+        })?,
+    };
+
+    let max_restarts = match setting("max_restarts") {
+        Yaml::BadValue => None,
+        Yaml::Integer(max) if max >= 0 => Some(max as u32),
+        _ => return Err("The max_restarts setting should be a number.".into()),
+    };
+
+    Ok(Some(Policy {
+        restart,
+        delay,
+        max_restarts,
+    }))
+}
+
+/// The command a supervised pane runs, with the `pre_window` commands ahead
+/// of it. The command is kept in a group of its own, so it's run the same
+/// when it's a list of commands.
+pub fn with_pre(pre: &[String], exec: &str) -> String {
+    if pre.is_empty() {
+        return exec.to_string();
+    };
+
+    format!("{} && {{\n{}\n}}", pre.join(" && "), exec)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use yaml_rust::YamlLoader;
+
+    fn yaml(text: &str) -> Yaml {
+        YamlLoader::load_from_str(text).unwrap().remove(0)
+    }
+
+    #[test]
+    fn expect_pane_settings_over_the_window() {
+        let window = yaml("{ restart: always, restart_delay: 5 }");
+        let pane = yaml("{ command: rails s, restart: on-failure, max_restarts: 3 }");
+
+        assert_eq!(
+            parse(&pane, &window),
+            Ok(Some(Policy {
+                restart: Restart::OnFailure,
+                delay: Duration::from_secs(5),
+                max_restarts: Some(3),
+            }))
+        )
+    }
+
+    #[test]
+    fn expect_no_policy_without_restart() {
+        assert_eq!(parse(&yaml("rails s"), &yaml("{ panes: [] }")), Ok(None));
+        assert_eq!(parse(&yaml("{ restart: no }"), &Yaml::BadValue), Ok(None))
+    }
+
+    #[test]
+    fn expect_pre_ahead_of_the_command() {
+        let pre = vec!["source .env".to_string(), "nvm use".to_string()];
+
+        assert_eq!(with_pre(&pre, "npm i && npm start"), "source .env && nvm use && {\nnpm i && npm start\n}");
+        assert_eq!(with_pre(&[], "rails s"), "rails s")
+    }
+
+    #[test]
+    fn expect_err_with_an_unknown_policy() {
+        assert!(parse(&yaml("{ restart: sometimes }"), &Yaml::BadValue).is_err())
+    }
+
+    #[test]
+    fn expect_on_failure_to_leave_clean_exits() {
+        let policy = Policy {
+            restart: Restart::OnFailure,
+            delay: DEFAULT_DELAY,
+            max_restarts: Some(2),
+        };
+
+        assert!(policy.restarts(1, 0));
+        assert!(!policy.restarts(0, 0));
+        assert!(!policy.restarts(1, 2))
+    }
+}
@@ -0,0 +1,272 @@
+//! The `wait_for` setting. A pane, or every pane of a window, holds its
+//! command back until something it depends on is ready.
+//!
+//! ```yaml
+//! windows:
+//!   - app:
+//!       panes:
+//!         - command: rails s
+//!           wait_for: { port: 5432 }
+//!         - command: tail -f log/development.log
+//!           wait_for: { file: log/development.log }
+//!   - db:
+//!       panes:
+//!         - command: postgres -D /usr/local/var/postgres
+//!         - command: psql
+//!           wait_for: { pane_output: "ready to accept connections", pane: db.0, timeout: 1m }
+//!         - command: rake db:migrate
+//!           wait_for: { command: pg_isready, timeout: 30s }
+//! ```
+//!
+//! A `pane` is found the way `muxed run` finds it: the window by its name in
+//! the project, and the pane by its place in the window counting from 0.
+use common::tmux::Server;
+use run;
+use std::fmt;
+use std::net::{TcpStream, ToSocketAddrs};
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+use std::rc::Rc;
+use std::time::Duration;
+use tmux::config::Config;
+use yaml_rust::Yaml;
+
+/// How long a condition has to become ready when no `timeout` is set.
+static DEFAULT_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// The host checked for a `port` when no `host` is set.
+static DEFAULT_HOST: &str = "localhost";
+
+/// What a pane waits for.
+/// `Port`: A host accepting connections on a port.
+/// `File`: A file existing, relative to the pane's path.
+/// `PaneOutput`: Text showing up in a pane, or in any pane of the session. The
+/// pane is named as in the project until it's resolved to a tmux target.
+/// `Command`: A command succeeding, run in the pane's path.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Condition {
+    Port(String, u16),
+    File(PathBuf),
+    PaneOutput(String, Option<String>),
+    Command(String),
+}
+
+impl Condition {
+    /// Check the condition once. `session` is the session the panes are in,
+    /// and `path` the directory of the waiting pane.
+    pub fn ready(&self, server: &Server, session: &str, path: &Option<Rc<PathBuf>>) -> bool {
+        let dir = path.as_ref().map_or_else(|| PathBuf::from("."), |p| p.to_path_buf());
+
+        match self {
+            Condition::Port(host, port) => (host.as_str(), *port)
+                .to_socket_addrs()
+                .map(|mut addrs| addrs.any(|a| TcpStream::connect_timeout(&a, Duration::from_millis(500)).is_ok()))
+                .unwrap_or(false),
+            Condition::File(file) => dir.join(file).exists(),
+            Condition::PaneOutput(text, pane) => {
+                panes(server, session, pane).iter().any(|p| capture(server, p).contains(text.as_str()))
+            }
+            Condition::Command(command) => Command::new("sh")
+                .args(["-c", command])
+                .current_dir(dir)
+                .stdout(Stdio::null())
+                .stderr(Stdio::null())
+                .status()
+                .map(|s| s.success())
+                .unwrap_or(false),
+        }
+    }
+}
+
+impl Condition {
+    /// Point the pane named in a `PaneOutput`, like `db.0`, at the pane tmux
+    /// opens for it in `session`.
+    pub fn resolve(self, session: &str, windows: &[(String, Yaml)], config: &Config) -> Result<Condition, String> {
+        match self {
+            Condition::PaneOutput(text, Some(pane)) => {
+                let target = run::target(session, &pane, windows, config)?;
+                Ok(Condition::PaneOutput(text, Some(target.arg_string().to_string())))
+            }
+            condition => Ok(condition),
+        }
+    }
+}
+
+impl fmt::Display for Condition {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Condition::Port(host, port) => write!(f, "port {} on {}", port, host),
+            Condition::File(file) => write!(f, "the file {}", file.display()),
+            Condition::PaneOutput(text, Some(pane)) => write!(f, "\"{}\" in pane {}", text, pane),
+            Condition::PaneOutput(text, None) => write!(f, "\"{}\" in a pane", text),
+            Condition::Command(command) => write!(f, "`{}` to succeed", command),
+        }
+    }
+}
+
+/// The condition in a `wait_for` setting, and how long to wait for it.
+pub fn parse(setting: &Yaml) -> Result<Option<(Condition, Duration)>, String> {
+    if setting.is_badvalue() {
+        return Ok(None);
+    };
+
+    let condition = if let Some(port) = setting["port"].as_i64() {
+        let host = setting["host"].as_str().unwrap_or(DEFAULT_HOST).to_string();
+        if port < 1 || port > 65535 {
+            return Err("wait_for port should be between 1 and 65535".into());
+        };
+        Condition::Port(host, port as u16)
+    } else if let Some(file) = setting["file"].as_str() {
+        Condition::File(PathBuf::from(file))
+    } else if let Some(text) = setting["pane_output"].as_str() {
+        Condition::PaneOutput(text.to_string(), setting["pane"].as_str().map(|p| p.to_string()))
+    } else if let Some(command) = setting["command"].as_str() {
+        Condition::Command(command.to_string())
+    } else {
+        return Err("wait_for takes a `port`, `file`, `pane_output` or `command`.".into());
+    };
+
+    let timeout = match setting["timeout"] {
+        Yaml::BadValue => DEFAULT_TIMEOUT,
+        Yaml::Integer(secs) => Duration::from_secs(secs as u64),
+        ref t => t.as_str().and_then(duration).ok_or_else(|| {
+            "The wait_for timeout should be seconds, or a number ending in ms, s or m. ex. 30s".to_string()
+        })?,
+    };
+
+    Ok(Some((condition, timeout)))
+}
+
+/// A duration like `500ms`, `30s` or `2m`.
+pub fn duration(text: &str) -> Option<Duration> {
+    let text = text.trim();
+    let digits: String = text.chars().take_while(|c| c.is_ascii_digit()).collect();
+    let number: u64 = digits.parse().ok()?;
+
+    match text[digits.len()..].trim() {
+        "ms" => Some(Duration::from_millis(number)),
+        "" | "s" => Some(Duration::from_secs(number)),
+        "m" => Some(Duration::from_secs(number * 60)),
+        _ => None,
+    }
+}
+
+/// The panes to search for output. The one given, or all of the session's.
+fn panes(server: &Server, session: &str, pane: &Option<String>) -> Vec<String> {
+    if let Some(pane) = pane {
+        return vec![pane.clone()];
+    };
+
+    let output = server
+        .command()
+        .args(["list-panes", "-s", "-t", session, "-F", "#{pane_id}"])
+        .output();
+
+    match output {
+        Ok(ref o) if o.status.success() => String::from_utf8_lossy(&o.stdout)
+            .lines()
+            .map(|l| l.to_string())
+            .collect(),
+        _ => vec![],
+    }
+}
+
+fn capture(server: &Server, pane: &str) -> String {
+    server
+        .command()
+        .args(["capture-pane", "-p", "-t", pane])
+        .output()
+        .map(|o| String::from_utf8_lossy(&o.stdout).to_string())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::net::TcpListener;
+    use yaml_rust::YamlLoader;
+
+    fn setting(text: &str) -> Yaml {
+        YamlLoader::load_from_str(text).unwrap().remove(0)
+    }
+
+    #[test]
+    fn expect_every_condition_to_parse() {
+        assert_eq!(
+            parse(&setting("port: 5432")),
+            Ok(Some((Condition::Port("localhost".to_string(), 5432), DEFAULT_TIMEOUT)))
+        );
+        assert_eq!(
+            parse(&setting("file: tmp/pids/server.pid")),
+            Ok(Some((Condition::File(PathBuf::from("tmp/pids/server.pid")), DEFAULT_TIMEOUT)))
+        );
+        assert_eq!(
+            parse(&setting("{ pane_output: ready, pane: db.1, timeout: 1m }")),
+            Ok(Some((
+                Condition::PaneOutput("ready".to_string(), Some("db.1".to_string())),
+                Duration::from_secs(60)
+            )))
+        );
+        assert_eq!(
+            parse(&setting("{ command: pg_isready, timeout: 5 }")),
+            Ok(Some((Condition::Command("pg_isready".to_string()), Duration::from_secs(5))))
+        )
+    }
+
+    #[test]
+    fn expect_panes_from_the_pane_base_index() {
+        let yaml = YamlLoader::load_from_str("windows: [app, { db: { panes: [postgres, psql] } }]").unwrap();
+        let windows = ::project::windows(&yaml[0]).unwrap();
+        let config = Config {
+            base_index: 1,
+            pane_base_index: 1,
+            ..Default::default()
+        };
+        let condition = Condition::PaneOutput("ready".to_string(), Some("db.0".to_string()));
+
+        assert_eq!(
+            condition.resolve("muxed", &windows, &config),
+            Ok(Condition::PaneOutput("ready".to_string(), Some("muxed:2.1".to_string())))
+        );
+        assert!(Condition::PaneOutput("ready".to_string(), Some("cache.0".to_string()))
+            .resolve("muxed", &windows, &config)
+            .is_err())
+    }
+
+    #[test]
+    fn expect_durations_with_units() {
+        assert_eq!(duration("500ms"), Some(Duration::from_millis(500)));
+        assert_eq!(duration("30s"), Some(Duration::from_secs(30)));
+        assert_eq!(duration("30"), Some(Duration::from_secs(30)));
+        assert_eq!(duration("an hour"), None)
+    }
+
+    #[test]
+    fn expect_err_with_an_unknown_condition() {
+        assert!(parse(&setting("socket: /tmp/db.sock")).is_err());
+        assert!(parse(&setting("{ port: 80, timeout: soon }")).is_err());
+        assert!(parse(&setting("port: 70000")).is_err());
+        assert!(parse(&setting("port: 0")).is_err())
+    }
+
+    #[test]
+    fn expect_an_open_port_to_be_ready() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let condition = Condition::Port("127.0.0.1".to_string(), port);
+
+        assert!(condition.ready(&Server::default(), "muxed", &None));
+        drop(listener);
+        assert!(!condition.ready(&Server::default(), "muxed", &None))
+    }
+
+    #[test]
+    fn expect_files_and_commands_relative_to_the_pane() {
+        let path = Some(Rc::new(PathBuf::from("/")));
+        let server = Server::default();
+
+        assert!(Condition::File(PathBuf::from("tmp")).ready(&server, "muxed", &path));
+        assert!(Condition::Command("test -d tmp".to_string()).ready(&server, "muxed", &path));
+        assert!(!Condition::Command("false".to_string()).ready(&server, "muxed", &path))
+    }
+}
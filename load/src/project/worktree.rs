@@ -0,0 +1,207 @@
+//! Opening a project on another branch. Each branch gets a git worktree of
+//! the project's repository and a session of its own, named
+//! `<project>-<branch>`, so branches can be worked on side by side.
+//!
+//! ```console
+//! $ muxed my_project --worktree feature/login
+//! ```
+use command::{Commands, SetEnvironment};
+use project::parser::expand_path;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use yaml_rust::yaml::Hash;
+use yaml_rust::Yaml;
+
+/// The variable naming the project a worktree session was opened from.
+pub static PROJECT_VAR: &str = "MUXED_PROJECT";
+
+/// The variable naming the branch a worktree session was opened on.
+pub static BRANCH_VAR: &str = "MUXED_WORKTREE";
+
+/// Point the project at the worktree for the branch, creating it if needed.
+/// The `root` moves in to the worktree, and the session is named after the
+/// branch. Returns the worktree's path.
+pub fn checkout(doc: &mut Yaml, project: &str, branch: &str) -> Result<PathBuf, String> {
+    let root = expand_path(&doc["root"])
+        .ok_or_else(|| "A worktree needs the project's `root` to be in a git repository.".to_string())?;
+
+    let top = git(&root, &["rev-parse", "--show-toplevel"])
+        .map_err(|e| format!("The project root {} isn't in a git repository. {}", root.display(), e))?;
+    let top = PathBuf::from(top.trim());
+
+    let worktree = match find(&top, branch)? {
+        Some(path) => path,
+        None => add(&top, branch)?,
+    };
+
+    // A root below the top of the repository stays below it in the worktree.
+    let relative = root.strip_prefix(&top).unwrap_or_else(|_| Path::new(""));
+    let name = format!("{}-{}", doc["name"].as_str().unwrap_or(project), branch);
+
+    if let Yaml::Hash(ref mut settings) = *doc {
+        set(settings, "root", &worktree.join(relative).display().to_string());
+        set(settings, "name", &session_name(&name));
+    };
+
+    Ok(worktree)
+}
+
+/// Tag the session with the project and branch, so `muxed list` can group it
+/// with its project.
+pub fn tag<'a>(commands: &mut Vec<Commands<'a>>, session: &'a str, project: &str, branch: &str) {
+    let mut session_at = None;
+    for (i, command) in commands.iter().enumerate() {
+        if let Commands::Session(_) = command {
+            session_at = Some(i);
+            break;
+        };
+    }
+
+    let position = match session_at {
+        Some(position) => position,
+        None => return,
+    };
+
+    let tags = vec![(PROJECT_VAR, project), (BRANCH_VAR, branch)];
+    for (i, (name, value)) in tags.into_iter().enumerate() {
+        let command = SetEnvironment::new(session, name.to_string(), value.to_string());
+        commands.insert(position + 1 + i, command.into());
+    }
+}
+
+/// tmux replaces `.` and `:` in session names, they separate targets.
+fn session_name(name: &str) -> String {
+    name.replace(&['.', ':'][..], "_")
+}
+
+/// The worktree already checked out on the branch.
+fn find(top: &Path, branch: &str) -> Result<Option<PathBuf>, String> {
+    let list = git(top, &["worktree", "list", "--porcelain"])?;
+    Ok(parse_worktrees(&list, branch))
+}
+
+/// Find the branch in `git worktree list --porcelain`. Worktrees are blocks
+/// of `worktree <path>` followed by `branch refs/heads/<branch>`.
+fn parse_worktrees(list: &str, branch: &str) -> Option<PathBuf> {
+    let head = format!("branch refs/heads/{}", branch);
+    let mut path = None;
+
+    for line in list.lines() {
+        if line.starts_with("worktree ") {
+            path = Some(PathBuf::from(line.trim_start_matches("worktree ")));
+        } else if line == head {
+            return path;
+        };
+    }
+
+    None
+}
+
+/// Add a worktree next to the repository, ex. `../my_project-feature-login`.
+/// Existing branches, local or remote, are checked out, others are created.
+fn add(top: &Path, branch: &str) -> Result<PathBuf, String> {
+    let dir_name = format!(
+        "{}-{}",
+        top.file_name().map_or_else(String::new, |n| n.to_string_lossy().to_string()),
+        branch.replace('/', "-")
+    );
+    let path = top.parent().unwrap_or(top).join(dir_name);
+    let path_arg = path.display().to_string();
+
+    let remote = format!("refs/remotes/*/{}", branch);
+    let local = format!("refs/heads/{}", branch);
+    let exists = !git(top, &["for-each-ref", "--format=%(refname)", &local, &remote])?
+        .trim()
+        .is_empty();
+
+    if exists {
+        git(top, &["worktree", "add", &path_arg, branch])?;
+    } else {
+        git(top, &["worktree", "add", "-b", branch, &path_arg])?;
+    };
+
+    Ok(path)
+}
+
+fn git(dir: &Path, args: &[&str]) -> Result<String, String> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(args)
+        .output()
+        .map_err(|e| format!("Couldn't run git: {}", e))?;
+
+    if !output.status.success() {
+        return Err(format!(
+            "`git {}` failed: {}",
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    };
+
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+fn set(settings: &mut Hash, key: &str, value: &str) {
+    settings.insert(Yaml::String(key.to_string()), Yaml::String(value.to_string()));
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use common::rand_names;
+    use std::fs;
+    use yaml_rust::YamlLoader;
+
+    fn repo() -> PathBuf {
+        let dir = rand_names::project_path().join("repo");
+        let _ = fs::create_dir_all(dir.join("api"));
+        let _ = fs::write(dir.join("api/README"), "api");
+
+        for args in &[
+            vec!["init", "-q"],
+            vec!["add", "."],
+            vec!["-c", "user.name=muxed", "-c", "user.email=muxed@example.com", "commit", "-qm", "init"],
+        ] {
+            let _ = git(&dir, args);
+        }
+
+        dir
+    }
+
+    #[test]
+    fn expect_branch_worktree_to_be_found() {
+        let list = "worktree /src/app\nHEAD 1a2b\nbranch refs/heads/main\n\nworktree /src/app-login\nHEAD 3c4d\nbranch refs/heads/feature/login\n";
+        assert_eq!(parse_worktrees(list, "feature/login"), Some(PathBuf::from("/src/app-login")));
+        assert_eq!(parse_worktrees(list, "feature"), None)
+    }
+
+    #[test]
+    fn expect_dots_and_colons_out_of_session_names() {
+        assert_eq!(session_name("app-release/1.2:rc"), "app-release/1_2_rc")
+    }
+
+    #[test]
+    fn expect_a_worktree_to_be_created_then_reused() {
+        let repo = repo();
+        let text = format!("root: {}\nwindows: ['vim']\n", repo.join("api").display());
+        let mut doc = YamlLoader::load_from_str(&text).unwrap().remove(0);
+        let mut again = doc.clone();
+
+        let created = checkout(&mut doc, "app", "feature/login");
+        let reused = checkout(&mut again, "app", "feature/login");
+        let _ = fs::remove_dir_all(repo.parent().unwrap());
+
+        let worktree = repo.with_file_name("repo-feature-login");
+        assert_eq!(created, Ok(worktree.clone()));
+        assert_eq!(reused, Ok(worktree.clone()));
+        assert_eq!(doc["root"].as_str(), Some(worktree.join("api").to_str().unwrap()));
+        assert_eq!(doc["name"].as_str(), Some("app-feature/login"))
+    }
+
+    #[test]
+    fn expect_err_outside_of_a_repository() {
+        let mut doc = YamlLoader::load_from_str("root: /\nwindows: ['vim']").unwrap().remove(0);
+        assert!(checkout(&mut doc, "app", "main").is_err())
+    }
+}
@@ -0,0 +1,170 @@
+//! Muxed record. Open a project and record the client attached to it in to an
+//! asciicast file, for playing back with asciinema. The client runs in a
+//! terminal of its own (a pty) muxed sits in front of, passing keys through
+//! and keeping a copy of everything it prints.
+//!
+//! ```console
+//! $ muxed record --output onboarding.cast my_project
+//! ```
+use asciicast::{Cast, Header};
+use common::args::Args;
+use libc;
+use std::env;
+use std::fs::File;
+use std::io::{self, Read, Write};
+use std::os::unix::io::{AsRawFd, FromRawFd, RawFd};
+use std::os::unix::process::CommandExt;
+use std::path::PathBuf;
+use std::process::Stdio;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use terminal::{set_window_size, window_size, RawMode};
+
+/// The size recorded when muxed isn't run in a terminal.
+static DEFAULT_SIZE: (u16, u16) = (80, 24);
+
+/// How often the terminal is checked for a new size.
+static RESIZE_INTERVAL: Duration = Duration::from_millis(100);
+
+pub fn exec(args: Args) -> Result<(), String> {
+    let path = args
+        .flag_output
+        .as_ref()
+        .map(PathBuf::from)
+        .unwrap_or_else(|| PathBuf::from(format!("{}.cast", args.arg_project)));
+
+    if path.exists() && !args.flag_f {
+        return Err(format!("The recording {} already exists. Use -f to overwrite it.", path.display()));
+    };
+
+    let (session, server) = ::open(args.clone(), false)?;
+
+    let stdin = io::stdin().as_raw_fd();
+    let size = window_size(stdin).unwrap_or(DEFAULT_SIZE);
+
+    let file = File::create(&path).map_err(|e| format!("We couldn't create {}: {}", path.display(), e))?;
+    let cast = Cast::new(file, &header(&session, size)).map_err(|e| format!("We couldn't write the recording: {}", e))?;
+    let cast = Arc::new(Mutex::new(cast));
+
+    let (master, slave) = pty(size).map_err(|e| format!("We couldn't open a terminal to record: {}", e))?;
+    let mut command = server.command();
+    command
+        .args(["attach-session", "-t", &session])
+        .env_remove("TMUX")
+        .stdin(Stdio::from(slave.try_clone().map_err(|e| e.to_string())?))
+        .stdout(Stdio::from(slave.try_clone().map_err(|e| e.to_string())?))
+        .stderr(Stdio::from(slave));
+
+    // The client takes the pty as its controlling terminal, so it's told when
+    // the size changes.
+    unsafe {
+        command.pre_exec(|| {
+            if libc::setsid() == -1 || libc::ioctl(0, libc::TIOCSCTTY as _, 0) == -1 {
+                return Err(io::Error::last_os_error());
+            };
+
+            Ok(())
+        });
+    }
+
+    let mut client = command.spawn().map_err(|e| format!("We couldn't attach to {}: {}", session, e))?;
+    drop(command);
+
+    let raw_mode = RawMode::enter(stdin);
+    let mut keys = master.try_clone().map_err(|e| e.to_string())?;
+    thread::spawn(move || io::copy(&mut io::stdin(), &mut keys));
+
+    let resizes = Arc::clone(&cast);
+    let master_fd = master.as_raw_fd();
+    thread::spawn(move || follow_size(stdin, master_fd, size, &resizes));
+
+    let recorded = copy_output(master, &cast);
+    let _ = client.wait();
+    drop(raw_mode);
+
+    recorded.map_err(|e| format!("We couldn't write the recording: {}", e))?;
+    println!("Recorded {} to {}", session, path.display());
+    Ok(())
+}
+
+/// The recording's header, with the current time and terminal.
+fn header(session: &str, (width, height): (u16, u16)) -> Header {
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+
+    let env = ["SHELL", "TERM"]
+        .iter()
+        .filter_map(|k| env::var(k).ok().map(|v| (k.to_string(), v)))
+        .collect();
+
+    Header {
+        width,
+        height,
+        timestamp,
+        title: session.to_string(),
+        env,
+    }
+}
+
+/// Show and record everything the client prints, until it exits. Reading
+/// the pty fails once the client closes it, which is how the end is found.
+fn copy_output(mut master: File, cast: &Mutex<Cast<File>>) -> io::Result<()> {
+    let mut stdout = io::stdout();
+    let mut buf = [0; 8192];
+
+    loop {
+        let read = match master.read(&mut buf) {
+            Ok(0) => return Ok(()),
+            Ok(read) => read,
+            Err(ref e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(_) => return Ok(()),
+        };
+
+        stdout.write_all(&buf[..read])?;
+        stdout.flush()?;
+        cast.lock().expect("The recording is only written here").output(&buf[..read])?;
+    }
+}
+
+/// Pass changes in the size of the terminal muxed runs in on to the client,
+/// recording them as they happen.
+fn follow_size(terminal: RawFd, master: RawFd, mut size: (u16, u16), cast: &Mutex<Cast<File>>) {
+    loop {
+        thread::sleep(RESIZE_INTERVAL);
+
+        match window_size(terminal) {
+            Some(now) if now != size => {
+                size = now;
+                set_window_size(master, size);
+
+                if let Ok(mut cast) = cast.lock() {
+                    let _ = cast.resize(size.0, size.1);
+                };
+            }
+            _ => {}
+        }
+    }
+}
+
+/// A new pty of the given size, its master and slave ends.
+fn pty((width, height): (u16, u16)) -> io::Result<(File, File)> {
+    let mut master = 0;
+    let mut slave = 0;
+    let size = libc::winsize {
+        ws_row: height,
+        ws_col: width,
+        ws_xpixel: 0,
+        ws_ypixel: 0,
+    };
+
+    unsafe {
+        if libc::openpty(&mut master, &mut slave, std::ptr::null_mut(), std::ptr::null(), &size) != 0 {
+            return Err(io::Error::last_os_error());
+        };
+
+        Ok((File::from_raw_fd(master), File::from_raw_fd(slave)))
+    }
+}
@@ -0,0 +1,180 @@
+//! Muxed restart. Sessions with panes set to `restart` get a `pane-died` hook
+//! calling this with the pane that exited. The pane's command is started again
+//! if its policy says so, otherwise the pane is left showing how it exited.
+use command::{Commands, SetHook};
+use common::args::Args;
+use common::shell::{run_shell, shell_quote};
+use common::tmux::Server;
+use project::supervise::{self, Policy, Restart};
+use std::env;
+use std::path::Path;
+use std::thread::sleep;
+use std::time::Duration;
+use tmux;
+
+/// The hook restarting panes.
+static HOOK: &str = "pane-died";
+
+/// A pane as the hook finds it.
+/// `dead`: Whether its command has exited.
+/// `status`: The status it exited with.
+/// `policy`: Its restart policy, if it has one.
+/// `restarts`: How many times it was restarted already.
+#[derive(Debug, PartialEq)]
+struct State {
+    dead: bool,
+    status: i32,
+    policy: Option<Policy>,
+    restarts: u32,
+}
+
+pub fn exec(args: Args) -> Result<(), String> {
+    let server = Server::from(&args);
+    let pane = args.arg_pane.as_str();
+
+    let format = [
+        "#{pane_dead}".to_string(),
+        "#{pane_dead_status}".to_string(),
+        format!("#{{{}}}", supervise::RESTART_OPTION),
+        format!("#{{{}}}", supervise::DELAY_OPTION),
+        format!("#{{{}}}", supervise::MAX_OPTION),
+        format!("#{{{}}}", supervise::COUNT_OPTION),
+    ]
+    .join("\t");
+
+    let output = tmux::checked(
+        tmux::call(&server, &["display-message", "-p", "-t", pane, &format]),
+        &format!("We couldn't look up the pane {}", pane),
+    )?;
+
+    let state = match parse_state(&String::from_utf8_lossy(&output.stdout)) {
+        Some(state) => state,
+        None => return Ok(()),
+    };
+
+    let policy = match state.policy {
+        Some(ref policy) if state.dead && policy.restarts(state.status, state.restarts) => policy,
+        _ => return Ok(()),
+    };
+
+    sleep(policy.delay);
+
+    let count = (state.restarts + 1).to_string();
+    tmux::checked(
+        tmux::call(
+            &server,
+            &["set-option", "-p", "-t", pane, supervise::COUNT_OPTION, &count, ";", "respawn-pane", "-t", pane],
+        ),
+        &format!("We couldn't restart the pane {}", pane),
+    )
+    .map(|_| ())
+}
+
+/// Give the session the hook restarting its panes, when any of them are set
+/// to restart.
+pub fn hook<'a>(commands: &mut Vec<Commands<'a>>, session: &'a str, server: &Server) -> Result<(), String> {
+    let supervised = commands.iter().any(supervised);
+    let position = commands.iter().position(|c| match c {
+        Commands::Session(_) => true,
+        _ => false,
+    });
+
+    if let (true, Some(position)) = (supervised, position) {
+        let exe = env::current_exe().map_err(|e| format!("We couldn't find muxed itself: {}", e))?;
+        let command = run_shell(&restart_command(&exe, server));
+        commands.insert(position + 1, SetHook::new(session, HOOK, command).into());
+    };
+
+    Ok(())
+}
+
+/// Whether the command supervises a pane, or holds back one that does.
+fn supervised(command: &Commands) -> bool {
+    match command {
+        Commands::Supervise(_) => true,
+        Commands::WaitFor(wait) => wait.then.iter().any(supervised),
+        _ => false,
+    }
+}
+
+/// The shell command restarting the pane that died, on the same server.
+fn restart_command(exe: &Path, server: &Server) -> String {
+    let mut command = vec![
+        shell_quote(&exe.to_string_lossy()),
+        "restart".to_string(),
+        "#{pane_id}".to_string(),
+    ];
+
+    let settings = vec![
+        ("--socket-name", &server.socket_name),
+        ("--socket-path", &server.socket_path),
+        ("--tmux-command", &server.command),
+    ];
+
+    for (flag, value) in settings {
+        if let Some(value) = value {
+            command.push(flag.to_string());
+            command.push(shell_quote(value));
+        };
+    }
+
+    command.join(" ")
+}
+
+/// Read the tab separated pane state. The options are empty when unset.
+fn parse_state(line: &str) -> Option<State> {
+    let fields: Vec<&str> = line.trim_end_matches('\n').split('\t').collect();
+
+    if fields.len() != 6 {
+        return None;
+    };
+
+    let policy = Restart::parse(fields[2]).map(|restart| Policy {
+        restart,
+        delay: Duration::from_millis(fields[3].parse().unwrap_or(0)),
+        max_restarts: fields[4].parse().ok(),
+    });
+
+    Some(State {
+        dead: fields[0] == "1",
+        status: fields[1].parse().unwrap_or(0),
+        policy,
+        restarts: fields[5].parse().unwrap_or(0),
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn expect_state_of_a_supervised_pane() {
+        assert_eq!(
+            parse_state("1\t3\ton-failure\t2000\t5\t1\n"),
+            Some(State {
+                dead: true,
+                status: 3,
+                policy: Some(Policy {
+                    restart: Restart::OnFailure,
+                    delay: Duration::from_secs(2),
+                    max_restarts: Some(5),
+                }),
+                restarts: 1,
+            })
+        )
+    }
+
+    #[test]
+    fn expect_no_policy_for_other_panes() {
+        let state = parse_state("1\t0\t\t\t\t\n").unwrap();
+        assert_eq!(state.policy, None)
+    }
+
+    #[test]
+    fn expect_restart_on_the_same_server() {
+        let server = Server::new(None, Some("work".to_string()), None, None);
+        let command = restart_command(Path::new("/usr/local/bin/muxed"), &server);
+
+        assert_eq!(command, "/usr/local/bin/muxed restart #{pane_id} --socket-name work")
+    }
+}
@@ -0,0 +1,184 @@
+//! Muxed run. Type a command in to a window or pane of a running project, for
+//! scripts that drive a project from outside of it. Windows are found by their
+//! name in the project file and panes by their place in the window, counting
+//! from 0, then targeted by index so names with dots in them don't confuse
+//! tmux.
+//!
+//! With `--wait` the command signals a tmux `wait-for` channel when it's done,
+//! having left its exit status in a server option, and muxed exits with it.
+//!
+//! ```console
+//! $ muxed run --wait my_project server.1 -- rake db:migrate
+//! ```
+use command::{Command, SendKeys};
+use common::args::Args;
+use common::project_paths::project_paths;
+use common::tmux::Server;
+use project;
+use std::process;
+use tmux;
+use tmux::config::Config;
+use tmux::target::{PaneTarget, Target, WindowTarget};
+use yaml_rust::Yaml;
+
+/// A running project, and the windows its project file describes.
+/// `session`: The session it's running in.
+/// `server`: The server it's running on.
+/// `windows`: The names and settings of its windows, in order.
+/// `config`: The server's settings, for the indexes windows and panes start at.
+pub struct Project {
+    pub session: String,
+    pub server: Server,
+    pub windows: Vec<(String, Yaml)>,
+    pub config: Config,
+}
+
+impl Project {
+    /// Read the project file for a project, erroring when it isn't running.
+    pub fn running(args: &Args) -> Result<Project, String> {
+        let project_paths = project_paths(args);
+        let yaml = project::read(&args.arg_project, &project_paths)?;
+
+        let session = yaml[0]["name"]
+            .as_str()
+            .unwrap_or(&args.arg_project)
+            .to_string();
+
+        let server = Server::from(args).or(project::server(&yaml[0]));
+
+        if !tmux::has_session(&server, &session).success() {
+            return Err(format!("The project {} isn't running.", session));
+        };
+
+        Ok(Project {
+            config: Config::from_string(tmux::get_config(&server)),
+            windows: project::windows(&yaml[0])?,
+            session,
+            server,
+        })
+    }
+}
+
+pub fn exec(args: Args) -> Result<i32, String> {
+    let project = Project::running(&args)?;
+    let server = &project.server;
+    let target = target(&project.session, &args.arg_target, &project.windows, &project.config)?;
+    let command = args.arg_command.join(" ");
+
+    if !args.flag_wait {
+        send(server, target, command, args.flag_debug)?;
+        return Ok(0);
+    };
+
+    let channel = format!("muxed_run_{}", process::id());
+    let option = format!("@{}", channel);
+    let tmux_command = server.command.as_deref().unwrap_or("tmux");
+    let signal = format!(
+        "{}; {} set-option -g {} $? \\; wait-for -S {}",
+        command, tmux_command, option, channel
+    );
+
+    send(server, target, signal, args.flag_debug)?;
+    call(server, &["wait-for", &channel])?;
+
+    let status = call(server, &["show-options", "-gqv", &option])?;
+    call(server, &["set-option", "-gu", &option])?;
+
+    status
+        .trim()
+        .parse()
+        .map_err(|_| format!("The command didn't leave an exit status, but `{}`.", status.trim()))
+}
+
+/// Find the window, or pane of a window, in `<window>[.<pane>]`. The window
+/// is a name from the project file, or its place in the file counting from 0.
+pub fn target(session: &str, target: &str, windows: &[(String, Yaml)], config: &Config) -> Result<Target, String> {
+    let (window, pane) = match target.rfind('.') {
+        Some(i) if windows.iter().all(|(name, _)| name != target) => match target[i + 1..].parse::<usize>() {
+            Ok(pane) => (&target[..i], Some(pane)),
+            Err(_) => (target, None),
+        },
+        _ => (target, None),
+    };
+
+    let position = windows
+        .iter()
+        .position(|(name, _)| name == window)
+        .or_else(|| window.parse::<usize>().ok().filter(|p| *p < windows.len()))
+        .ok_or_else(|| format!("The project has no window named {}.", window))?;
+
+    let index = (config.base_index + position).to_string();
+
+    match pane {
+        Some(pane) => {
+            let panes = windows[position].1["panes"].as_vec().map_or(1, |p| p.len());
+
+            if pane >= panes {
+                return Err(format!("The window {} has no pane {}.", window, pane));
+            };
+
+            Ok(Target::PaneTarget(PaneTarget::new(session, &index, config.pane_base_index + pane)))
+        }
+        None => Ok(Target::WindowTarget(WindowTarget::new(session, &index))),
+    }
+}
+
+/// Type a command in to a window or pane, and run it.
+pub fn send(server: &Server, target: Target, command: String, debug: bool) -> Result<(), String> {
+    tmux::checked(SendKeys::new(target, command).call(server, debug), "We couldn't send the command").map(|_| ())
+}
+
+fn call(server: &Server, args: &[&str]) -> Result<String, String> {
+    let output = tmux::checked(tmux::call(server, args), "Had a problem running commands for tmux")?;
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use yaml_rust::YamlLoader;
+
+    fn windows() -> Vec<(String, Yaml)> {
+        let yaml = YamlLoader::load_from_str(
+            "windows:
+               - editor: { panes: [vim, ls] }
+               - rails.log: tail -f log/development.log
+               - git",
+        )
+        .unwrap();
+
+        project::windows(&yaml[0]).unwrap()
+    }
+
+    fn config(base_index: usize, pane_base_index: usize) -> Config {
+        Config {
+            base_index,
+            pane_base_index,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn expect_window_names_to_resolve_to_indexes() {
+        let target = target("app", "git", &windows(), &config(1, 0)).unwrap();
+        assert_eq!(target.arg_string(), "app:3")
+    }
+
+    #[test]
+    fn expect_panes_from_the_pane_base_index() {
+        let target = target("app", "editor.1", &windows(), &config(0, 1)).unwrap();
+        assert_eq!(target.arg_string(), "app:0.2")
+    }
+
+    #[test]
+    fn expect_dotted_window_names_to_be_kept_whole() {
+        let target = target("app", "rails.log", &windows(), &config(0, 0)).unwrap();
+        assert_eq!(target.arg_string(), "app:1")
+    }
+
+    #[test]
+    fn expect_err_for_unknown_windows_and_panes() {
+        assert!(target("app", "server", &windows(), &config(0, 0)).is_err());
+        assert!(target("app", "editor.2", &windows(), &config(0, 0)).is_err())
+    }
+}
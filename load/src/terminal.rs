@@ -0,0 +1,69 @@
+//! The terminal muxed runs in, for the commands that draw on it or pass it on
+//! to a client of their own.
+use libc;
+use std::mem;
+use std::os::unix::io::RawFd;
+
+/// The terminal's settings, put back once muxed is done with it.
+pub struct RawMode {
+    fd: RawFd,
+    saved: libc::termios,
+}
+
+impl RawMode {
+    /// Read every key as it's pressed, without echoing it. None when `fd`
+    /// isn't a terminal.
+    pub fn enter(fd: RawFd) -> Option<RawMode> {
+        unsafe {
+            if libc::isatty(fd) == 0 {
+                return None;
+            };
+
+            let mut saved: libc::termios = mem::zeroed();
+            if libc::tcgetattr(fd, &mut saved) != 0 {
+                return None;
+            };
+
+            let mut raw = saved;
+            libc::cfmakeraw(&mut raw);
+            libc::tcsetattr(fd, libc::TCSANOW, &raw);
+
+            Some(RawMode { fd, saved })
+        }
+    }
+}
+
+impl Drop for RawMode {
+    fn drop(&mut self) {
+        unsafe {
+            libc::tcsetattr(self.fd, libc::TCSANOW, &self.saved);
+        }
+    }
+}
+
+/// The columns and rows of a terminal, None when `fd` isn't one.
+pub fn window_size(fd: RawFd) -> Option<(u16, u16)> {
+    unsafe {
+        let mut size: libc::winsize = mem::zeroed();
+
+        if libc::ioctl(fd, libc::TIOCGWINSZ as _, &mut size) != 0 || size.ws_col == 0 {
+            return None;
+        };
+
+        Some((size.ws_col, size.ws_row))
+    }
+}
+
+/// Resize a terminal, telling whatever runs in it.
+pub fn set_window_size(fd: RawFd, (width, height): (u16, u16)) {
+    let size = libc::winsize {
+        ws_row: height,
+        ws_col: width,
+        ws_xpixel: 0,
+        ws_ypixel: 0,
+    };
+
+    unsafe {
+        libc::ioctl(fd, libc::TIOCSWINSZ as _, &size);
+    }
+}
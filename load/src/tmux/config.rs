@@ -5,31 +5,161 @@
 use std::collections::HashMap;
 use std::str::FromStr;
 
+/// A single option value as printed by `show-options`. Tmux prints flags as
+/// `on`/`off`, numbers bare, and strings bare or quoted. Array options like
+/// `status-format[0]` are printed one line per index and collected here.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Flag(bool),
+    Number(i64),
+    Text(String),
+    Array(Vec<String>),
+}
+
+impl Value {
+    fn from_raw(raw: &str) -> Value {
+        let (value, quoted) = unquote(raw);
+
+        if quoted {
+            return Value::Text(value);
+        };
+
+        match value.as_str() {
+            "on" => Value::Flag(true),
+            "off" => Value::Flag(false),
+            x => match i64::from_str(x) {
+                Ok(n) => Value::Number(n),
+                Err(_) => Value::Text(value),
+            },
+        }
+    }
+
+    pub fn as_bool(&self) -> Option<bool> {
+        match *self {
+            Value::Flag(b) => Some(b),
+            _ => None,
+        }
+    }
+
+    pub fn as_number(&self) -> Option<i64> {
+        match *self {
+            Value::Number(n) => Some(n),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match *self {
+            Value::Text(ref s) => Some(s),
+            _ => None,
+        }
+    }
+
+    pub fn as_array(&self) -> Option<&[String]> {
+        match *self {
+            Value::Array(ref a) => Some(a),
+            _ => None,
+        }
+    }
+}
+
 /// A simple struct for accessing parsed config options we want to know about.
-#[derive(Debug, Clone)]
+/// Every other option the server reported, including user `@options`, is kept
+/// in `options` keyed by name.
+#[derive(Debug, Clone, Default)]
 pub struct Config {
     pub base_index: usize,
     pub pane_base_index: usize,
+    pub options: HashMap<String, Value>,
 }
 
-/// The parser of config options. A string of output is passed in. It's simple
-/// key value pairs in the format of `key value\n`
+/// The parser of config options. A string of output is passed in. It's key
+/// value pairs in the format of `key value\n`. The value may be quoted, or
+/// missing entirely for empty array options.
 impl Config {
     pub fn from_string(options: String) -> Config {
-        let lines = options.lines();
-        let mut config: HashMap<&str, &str> = HashMap::new();
+        let mut config: HashMap<String, Value> = HashMap::new();
 
-        for line in lines {
-            let opt: Vec<&str> = line.split(' ').collect();
-            config.insert(opt[0], opt[1]);
+        for line in options.lines().map(|l| l.trim()).filter(|l| !l.is_empty()) {
+            let mut opt = line.splitn(2, ' ');
+            let key = opt.next().unwrap_or("");
+            let raw = opt.next().unwrap_or("");
+
+            match array_key(key) {
+                Some(name) => {
+                    let entry = config
+                        .entry(name.to_string())
+                        .or_insert_with(|| Value::Array(vec![]));
+
+                    if let Value::Array(ref mut values) = *entry {
+                        values.push(unquote(raw).0);
+                    };
+                }
+                None if raw.is_empty() => {
+                    config.insert(key.to_string(), Value::Array(vec![]));
+                }
+                None => {
+                    config.insert(key.to_string(), Value::from_raw(raw));
+                }
+            };
         }
 
+        let index = |key: &str| {
+            config
+                .get(key)
+                .and_then(|v| v.as_number())
+                .map_or(0, |n| n as usize)
+        };
+
         Config {
-            base_index: usize::from_str(config.get("base-index").unwrap_or(&"0")).unwrap(),
-            pane_base_index: usize::from_str(config.get("pane-base-index").unwrap_or(&"0"))
-                .unwrap(),
+            base_index: index("base-index"),
+            pane_base_index: index("pane-base-index"),
+            options: config,
         }
     }
+
+    /// Look up any option by its name. ex. `default-shell` or `@my-option`.
+    pub fn get(&self, name: &str) -> Option<&Value> {
+        self.options.get(name)
+    }
+}
+
+/// Array options are printed with their index. ex. `status-format[1]`.
+/// Returns the option name without the index.
+fn array_key(key: &str) -> Option<&str> {
+    if !key.ends_with(']') {
+        return None;
+    };
+
+    key.find('[').map(|i| &key[..i])
+}
+
+/// Remove the quoting tmux adds to values containing spaces or special
+/// characters. Double quoted values may contain backslash escapes, single
+/// quoted values are taken literally. Returns whether the value was quoted.
+fn unquote(raw: &str) -> (String, bool) {
+    let quote = match raw.chars().next() {
+        Some(q) if (q == '"' || q == '\'') && raw.len() > 1 && raw.ends_with(q) => q,
+        _ => return (raw.to_string(), false),
+    };
+
+    let inner = &raw[1..raw.len() - 1];
+
+    if quote == '\'' {
+        return (inner.to_string(), true);
+    };
+
+    let mut value = String::with_capacity(inner.len());
+    let mut chars = inner.chars();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => value.extend(chars.next()),
+            _ => value.push(c),
+        };
+    }
+
+    (value, true)
 }
 
 #[test]
@@ -73,3 +203,56 @@ fn expect_missing_pane_base_index_0() {
     let config = Config::from_string(output);
     assert_eq!(config.pane_base_index, 0)
 }
+
+#[test]
+fn expect_no_panic_on_option_without_value() {
+    let output = "pane-colours\nbase-index 1".to_string();
+    let config = Config::from_string(output);
+    assert_eq!(config.base_index, 1)
+}
+
+#[test]
+fn expect_quoted_value_to_be_unquoted() {
+    let output = "lock-command \"lock -np\"".to_string();
+    let config = Config::from_string(output);
+    assert_eq!(config.get("lock-command"), Some(&Value::Text("lock -np".to_string())))
+}
+
+#[test]
+fn expect_escapes_in_quoted_value() {
+    let output = r##"word-separators "!\"#$\\""##.to_string();
+    let config = Config::from_string(output);
+    assert_eq!(config.get("word-separators").and_then(|v| v.as_str()), Some(r##"!"#$\"##))
+}
+
+#[test]
+fn expect_single_quoted_empty_value() {
+    let output = "default-command ''".to_string();
+    let config = Config::from_string(output);
+    assert_eq!(config.get("default-command").and_then(|v| v.as_str()), Some(""))
+}
+
+#[test]
+fn expect_flags_and_numbers() {
+    let output = "renumber-windows on\nhistory-limit 2000\ndefault-shell /bin/bash".to_string();
+    let config = Config::from_string(output);
+    assert_eq!(config.get("renumber-windows").and_then(|v| v.as_bool()), Some(true));
+    assert_eq!(config.get("history-limit").and_then(|v| v.as_number()), Some(2000));
+    assert_eq!(config.get("default-shell").and_then(|v| v.as_str()), Some("/bin/bash"))
+}
+
+#[test]
+fn expect_array_options_to_be_collected() {
+    let output = "status-format[0] \"#[align=left]\"\nstatus-format[1] \"#[align=centre]\""
+        .to_string();
+    let config = Config::from_string(output);
+    let expected = ["#[align=left]".to_string(), "#[align=centre]".to_string()];
+    assert_eq!(config.get("status-format").and_then(|v| v.as_array()), Some(&expected[..]))
+}
+
+#[test]
+fn expect_user_options() {
+    let output = "@project-colour \"bright blue\"".to_string();
+    let config = Config::from_string(output);
+    assert_eq!(config.get("@project-colour").and_then(|v| v.as_str()), Some("bright blue"))
+}
@@ -1,36 +1,56 @@
 //! The interface for interacting with TMUX sessions. All the commands that are
 /// built up during the parsing phase get matched to functions here. The
-/// functions in this module all build up strings the get passed to a private
-/// call. The private call converts them to `CStrings` and makes an "unsafe" system
-/// call. All functions go through this `call` function as a common gateway to
-/// system calls and can all be easily logged there.
+/// functions in this module all build up strings the get passed to a common
+/// call. The call hands them to `std::process::Command` for execution. All
+/// functions go through this `call` function as a common gateway to system
+/// calls and can all be easily logged there.
 pub mod config;
 pub mod target;
+pub mod version;
 
-use libc::system;
-use std::ffi::CString;
+use common::tmux::Server;
+use std::env;
 use std::io;
-use std::os::unix::process::ExitStatusExt;
-use std::process::{Command, ExitStatus, Output};
-
-/// The program to call commands on.
-static TMUX_NAME: &str = "tmux";
+use std::path::Path;
+use std::process::{ExitStatus, Output};
+use tmux::version::Version;
 
 /// The gateway to calling any functions on tmux. Most public functions in this
 /// module will be fed through this `call` function. This safely creates a new
 /// thread to execute the command on. We say "Most" public functions will use
-/// this as `attach` specificaly does not use it.
+/// this as `attach` specificaly does not use it, it needs the users TTY.
 ///
+/// server: The tmux program and socket to send the command to.
 /// args: The command we will send to tmux on the host system for execution.
 ///
 /// # Examples
 ///
 /// ```
-/// let _ = call(&["new-window", "-t", "muxed", "-c", "~/Projects/muxed/"]);
+/// let server = Server::default();
+/// let _ = call(&server, &["new-window", "-t", "muxed", "-c", "~/Projects/muxed/"]);
 /// ```
-pub fn call(args: &[&str]) -> Result<Output, io::Error> {
+pub fn call(server: &Server, args: &[&str]) -> Result<Output, io::Error> {
     //println!("{:?}", &args);
-    Command::new(TMUX_NAME).args(args).output()
+    server.command().args(args).output()
+}
+
+/// Check a call to tmux went through. The error starts with `problem`,
+/// followed by tmux's own message when tmux turned the command down.
+///
+/// # Examples
+///
+/// ```
+/// tmux::checked(tmux::call(&server, &["kill-session", "-t", "muxed"]), "We couldn't close muxed");
+/// => Err("We couldn't close muxed: can't find session: muxed")
+/// ```
+pub fn checked(output: Result<Output, io::Error>, problem: &str) -> Result<Output, String> {
+    let output = output.map_err(|e| format!("{}: {}", problem, e))?;
+
+    if !output.status.success() {
+        return Err(format!("{}: {}", problem, String::from_utf8_lossy(&output.stderr).trim()));
+    };
+
+    Ok(output)
 }
 
 /// Has session is used firgure out if a named session is already running.
@@ -38,14 +58,14 @@ pub fn call(args: &[&str]) -> Result<Output, io::Error> {
 /// # Examples
 ///
 /// ```
-/// tmux::has_session("muxed".to_string());
+/// tmux::has_session(&server, "muxed");
 /// => ExitStatus
 /// ```
 ///
 /// `target`: A string represented by the `{named_session}`
-pub fn has_session(target: &str) -> ExitStatus {
-    let output =
-        call(&["has-session", "-t", target]).expect("failed to see if the session existed");
+pub fn has_session(server: &Server, target: &str) -> ExitStatus {
+    let output = call(server, &["has-session", "-t", target])
+        .expect("failed to see if the session existed");
     output.status
 }
 
@@ -54,37 +74,110 @@ pub fn has_session(target: &str) -> ExitStatus {
 /// # Examples
 ///
 /// ```
-/// tmux::get_config();
+/// tmux::get_config(&server);
 /// => "some-option false\npane-base-index 0"
 /// ```
-pub fn get_config() -> String {
-    let output = call(&["start-server", ";", "show-options", "-g", ";", "show-options", "-g", "-w"])
+pub fn get_config(server: &Server) -> String {
+    let output = call(server, &["start-server", ";", "show-options", "-g", ";", "show-options", "-g", "-w"])
       .expect("couldn't get tmux options");
     String::from_utf8_lossy(&output.stdout).to_string()
 }
 
+/// Ask tmux for its version, this is done once per run. Returns None when the
+/// version couldn't be read or the build isn't a numbered release.
+///
+/// # Examples
+///
+/// ```
+/// tmux::version(&server);
+/// => Some(Version { major: 3, minor: 3, patch: 1 })
+/// ```
+pub fn version(server: &Server) -> Option<Version> {
+    let output = server.command().arg("-V").output().ok()?;
+    Version::parse(&String::from_utf8_lossy(&output.stdout))
+}
+
+/// Find out if muxed is being run from within a tmux client of the given
+/// server. Tmux exports `$TMUX` to every process started inside one of its
+/// panes, in the format `{socket_path},{pid},{session_index}`. A client of a
+/// different server can't be switched over so it doesn't count.
+///
+/// # Examples
+///
+/// ```
+/// tmux::inside_tmux(&server);
+/// => true
+/// ```
+pub fn inside_tmux(server: &Server) -> bool {
+    match env::var("TMUX") {
+        Ok(ref tmux) if !tmux.is_empty() => same_socket(server, tmux),
+        _ => false,
+    }
+}
+
+/// Compare the socket found in a `$TMUX` value against the socket the server
+/// settings point at. Without any socket settings tmux uses `default`.
+fn same_socket(server: &Server, tmux: &str) -> bool {
+    let socket = Path::new(tmux.split(',').next().unwrap_or(""));
+
+    match (server.socket_path.as_ref(), server.socket_name.as_ref()) {
+        (Some(path), _) => socket == Path::new(path),
+        (None, Some(name)) => socket.file_name().map_or(false, |f| f == name.as_str()),
+        (None, None) => socket.file_name().map_or(false, |f| f == "default"),
+    }
+}
+
 /// Attach is called as the last function in a set of commands. After the tmux
 /// env has been setup by all previous commands this attaches the user to their
-/// daemonized tmux session.
+/// daemonized tmux session. The client inherits our TTY so tmux can take over
+/// the terminal. `$TMUX` is cleared for the child so an explicit request for a
+/// new client from inside tmux isn't refused as nesting.
 ///
 /// # Examples
 ///
 /// ```
-/// let session_name = "muxed".to_string();
-/// tmux::attach(muxed);
+/// tmux::attach(&server, &["attach", "-t", "muxed"]);
 /// ```
-/// `session_name: The active tmux session name.
-pub fn attach(args: &[&str]) -> Result<Output, io::Error> {
-    let arg_string = [&[TMUX_NAME], &args[..]].concat().join(" ");
-    let system_call = CString::new(arg_string).unwrap();
-    // println!("{:?}", arg_string.clone());
-    unsafe {
-        let output = system(system_call.as_ptr());
-
-        Ok(Output {
-            status: ExitStatus::from_raw(output),
-            stdout: vec![],
-            stderr: vec![],
-        })
+/// `args`: The attach command and the target session.
+pub fn attach(server: &Server, args: &[&str]) -> Result<Output, io::Error> {
+    let status = server
+        .command()
+        .args(args)
+        .env_remove("TMUX")
+        .status()?;
+
+    Ok(Output {
+        status,
+        stdout: vec![],
+        stderr: vec![],
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn expect_default_socket_to_match_default_server() {
+        let server = Server::default();
+        assert!(same_socket(&server, "/tmp/tmux-1000/default,4242,0"))
+    }
+
+    #[test]
+    fn expect_named_socket_not_to_match_default_server() {
+        let server = Server::default();
+        assert!(!same_socket(&server, "/tmp/tmux-1000/work,4242,0"))
+    }
+
+    #[test]
+    fn expect_named_socket_to_match_socket_name() {
+        let server = Server::new(None, Some("work".to_string()), None, None);
+        assert!(same_socket(&server, "/tmp/tmux-1000/work,4242,0"))
+    }
+
+    #[test]
+    fn expect_socket_path_to_match_socket_path() {
+        let server = Server::new(None, None, Some("/tmp/work.sock".to_string()), None);
+        assert!(same_socket(&server, "/tmp/work.sock,4242,0"))
     }
 }
@@ -1,15 +1,18 @@
 //! The structures used to manage commands sent over to tmux.
 
+use std::collections::HashMap;
 use std::fmt;
-use std::usize;
 
 /// A targeted pane for a tmux session
+/// `position`: Where the pane sits in the project file as `(window, pane)`,
+/// counting from 0. Used to target the pane by id once tmux has created it.
 #[derive(Debug, Clone)]
 pub struct PaneTarget {
     pub session: String,
     pub window: String,
     pub pane_index: usize,
     pub arg_string: String,
+    pub position: Option<(usize, usize)>,
 }
 
 impl PaneTarget {
@@ -19,8 +22,15 @@ impl PaneTarget {
             window: window.to_string(),
             pane_index,
             arg_string: format!("{}:{}.{}", session, window, pane_index),
+            position: None,
         }
     }
+
+    /// Set the position of the pane in the project file.
+    pub fn at(mut self, window: usize, pane: usize) -> PaneTarget {
+        self.position = Some((window, pane));
+        self
+    }
 }
 
 impl fmt::Display for PaneTarget {
@@ -30,11 +40,14 @@ impl fmt::Display for PaneTarget {
 }
 
 /// A targeted window for a tmux session
+/// `position`: Where the window sits in the project file, counting from 0.
+/// Used to target the window by id once tmux has created it.
 #[derive(Debug, Clone)]
 pub struct WindowTarget {
     pub session: String,
     pub window: String,
     pub arg_string: String,
+    pub position: Option<usize>,
 }
 
 impl WindowTarget {
@@ -43,8 +56,15 @@ impl WindowTarget {
             session: session.to_string(),
             window: window.to_string(),
             arg_string: format!("{}:{}", session, window),
+            position: None,
         }
     }
+
+    /// Set the position of the window in the project file.
+    pub fn at(mut self, window: usize) -> WindowTarget {
+        self.position = Some(window);
+        self
+    }
 }
 
 impl fmt::Display for WindowTarget {
@@ -89,3 +109,119 @@ impl Target {
         }
     }
 }
+
+/// The ids tmux assigned to the windows and panes we've created. Names are
+/// ambiguous targets: they can repeat, contain `.` or `:`, and change with
+/// `automatic-rename`. Ids can't, so once a window or pane exists every later
+/// command targets it by its `@id` or `%id`.
+/// `windows`: Window ids in the order the windows were created.
+/// `panes`: Pane ids keyed by their `(window, pane)` position.
+#[derive(Debug, Default)]
+pub struct Ids {
+    pub windows: Vec<Option<String>>,
+    pub panes: HashMap<(usize, usize), String>,
+}
+
+impl Ids {
+    /// Record a new window from the output of `-F '#{window_id} #{pane_id}'`.
+    /// Windows are recorded even when no id was printed, so the following
+    /// windows keep their positions.
+    pub fn add_window(&mut self, output: &str) {
+        let position = self.windows.len();
+        let mut ids = output.split_whitespace();
+
+        self.windows.push(ids.next().map(|id| id.to_string()));
+
+        if let Some(pane) = ids.next() {
+            self.panes.insert((position, 0), pane.to_string());
+        };
+    }
+
+    /// Record a new pane from the output of `-F '#{pane_id}'`.
+    pub fn add_pane(&mut self, position: (usize, usize), output: &str) {
+        if let Some(pane) = output.split_whitespace().next() {
+            self.panes.insert(position, pane.to_string());
+        };
+    }
+
+    pub fn resolve_window(&self, target: &mut WindowTarget) {
+        let id = target
+            .position
+            .and_then(|p| self.windows.get(p))
+            .and_then(|id| id.as_ref());
+
+        if let Some(id) = id {
+            target.arg_string = id.clone();
+        };
+    }
+
+    pub fn resolve_pane(&self, target: &mut PaneTarget) {
+        if let Some(id) = target.position.and_then(|p| self.panes.get(&p)) {
+            target.arg_string = id.clone();
+        };
+    }
+
+    pub fn resolve(&self, target: &mut Target) {
+        match *target {
+            Target::PaneTarget(ref mut t) => self.resolve_pane(t),
+            Target::WindowTarget(ref mut t) => self.resolve_window(t),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn expect_window_to_resolve_to_id() {
+        let mut ids = Ids::default();
+        ids.add_window("@4 %7\n");
+        ids.add_window("@5 %8\n");
+
+        let mut target = WindowTarget::new("muxed", "my.window").at(1);
+        ids.resolve_window(&mut target);
+        assert_eq!(target.arg_string, "@5")
+    }
+
+    #[test]
+    fn expect_first_pane_to_resolve_from_window_output() {
+        let mut ids = Ids::default();
+        ids.add_window("@4 %7\n");
+
+        let mut target = PaneTarget::new("muxed", "editor", 0).at(0, 0);
+        ids.resolve_pane(&mut target);
+        assert_eq!(target.arg_string, "%7")
+    }
+
+    #[test]
+    fn expect_split_pane_to_resolve() {
+        let mut ids = Ids::default();
+        ids.add_window("@4 %7\n");
+        ids.add_pane((0, 1), "%9\n");
+
+        let mut target = PaneTarget::new("muxed", "editor", 1).at(0, 1);
+        ids.resolve_pane(&mut target);
+        assert_eq!(target.arg_string, "%9")
+    }
+
+    #[test]
+    fn expect_failed_window_to_keep_positions() {
+        let mut ids = Ids::default();
+        ids.add_window("");
+        ids.add_window("@5 %8\n");
+
+        let mut target = WindowTarget::new("muxed", "second").at(1);
+        ids.resolve_window(&mut target);
+        assert_eq!(target.arg_string, "@5")
+    }
+
+    #[test]
+    fn expect_unknown_target_to_keep_name() {
+        let ids = Ids::default();
+
+        let mut target = WindowTarget::new("muxed", "editor").at(0);
+        ids.resolve_window(&mut target);
+        assert_eq!(target.arg_string, "muxed:editor")
+    }
+}
@@ -0,0 +1,146 @@
+//! The tmux version we're talking to
+/// Newer tmux options don't exist on older servers and tmux doesn't always
+/// complain when it sees them. Commands declare the features they rely on so
+/// we can check them against the running version before building a session.
+use std::fmt;
+use std::str::FromStr;
+
+/// A comparable tmux version. Tmux releases are numbered `{major}.{minor}`
+/// with an optional letter for patch releases. ex. `3.3a`. The letter is
+/// stored as a number, `a` being 1 and no letter being 0.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Version {
+    pub major: u32,
+    pub minor: u32,
+    pub patch: u32,
+}
+
+impl Version {
+    pub fn new(major: u32, minor: u32, patch: u32) -> Version {
+        Version {
+            major,
+            minor,
+            patch,
+        }
+    }
+
+    /// Parse the output of `tmux -V`. ex. `tmux 3.3a`, `tmux 3.2-rc3` or
+    /// `tmux next-3.4`. Builds without a version number like `tmux master`
+    /// return None.
+    pub fn parse(output: &str) -> Option<Version> {
+        let version = output.trim().trim_start_matches("tmux").trim();
+        let version = version.trim_start_matches("next-");
+
+        let mut parts = version.splitn(2, '.');
+        let major = u32::from_str(parts.next()?).ok()?;
+        let rest = parts.next()?;
+
+        let digits: String = rest.chars().take_while(|c| c.is_ascii_digit()).collect();
+        let minor = u32::from_str(&digits).ok()?;
+
+        let patch = match rest[digits.len()..].chars().next() {
+            Some(c) if c.is_ascii_lowercase() => c as u32 - 'a' as u32 + 1,
+            _ => 0,
+        };
+
+        Some(Version::new(major, minor, patch))
+    }
+}
+
+impl fmt::Display for Version {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}.{}", self.major, self.minor)?;
+
+        if self.patch > 0 {
+            write!(f, "{}", (b'a' + (self.patch - 1) as u8) as char)?;
+        };
+
+        Ok(())
+    }
+}
+
+/// A tmux feature a command relies on, and the version it first shipped in.
+/// `name`: A description of the feature for error messages. ex. `new-window -c`.
+/// `since`: The first version of tmux supporting it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Feature {
+    pub name: &'static str,
+    pub since: Version,
+}
+
+impl Feature {
+    pub fn new(name: &'static str, since: Version) -> Feature {
+        Feature { name, since }
+    }
+
+    /// Check the feature is available in the given version of tmux.
+    pub fn check(&self, version: &Version) -> Result<(), String> {
+        if self.since > *version {
+            return Err(format!(
+                "`{}` requires tmux {} or newer, but tmux {} was found.",
+                self.name, self.since, version
+            ));
+        };
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn expect_version_with_patch_letter() {
+        let version = Version::parse("tmux 3.3a\n");
+        assert_eq!(version, Some(Version::new(3, 3, 1)))
+    }
+
+    #[test]
+    fn expect_version_without_patch_letter() {
+        let version = Version::parse("tmux 2.9");
+        assert_eq!(version, Some(Version::new(2, 9, 0)))
+    }
+
+    #[test]
+    fn expect_release_candidate_to_ignore_suffix() {
+        let version = Version::parse("tmux 3.2-rc3");
+        assert_eq!(version, Some(Version::new(3, 2, 0)))
+    }
+
+    #[test]
+    fn expect_next_version() {
+        let version = Version::parse("tmux next-3.4");
+        assert_eq!(version, Some(Version::new(3, 4, 0)))
+    }
+
+    #[test]
+    fn expect_none_for_master() {
+        let version = Version::parse("tmux master");
+        assert_eq!(version, None)
+    }
+
+    #[test]
+    fn expect_versions_to_compare() {
+        assert!(Version::new(3, 3, 1) > Version::new(3, 3, 0));
+        assert!(Version::new(3, 0, 0) > Version::new(2, 9, 1));
+        assert!(Version::new(2, 10, 0) > Version::new(2, 9, 0))
+    }
+
+    #[test]
+    fn expect_display_with_patch_letter() {
+        assert_eq!(Version::new(3, 3, 1).to_string(), "3.3a")
+    }
+
+    #[test]
+    fn expect_err_when_feature_is_newer() {
+        let feature = Feature::new("select-pane -T", Version::new(2, 6, 0));
+        assert!(feature.check(&Version::new(2, 5, 0)).is_err())
+    }
+
+    #[test]
+    fn expect_ok_when_feature_is_older() {
+        let feature = Feature::new("select-pane -T", Version::new(2, 6, 0));
+        assert!(feature.check(&Version::new(2, 6, 0)).is_ok())
+    }
+}
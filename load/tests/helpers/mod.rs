@@ -2,6 +2,7 @@
 
 use common::args::Args;
 use common::rand_names;
+use common::tmux::Server;
 use rand::random;
 use snapshot::tmux;
 use std::fs;
@@ -59,7 +60,7 @@ pub fn test_with_contents(contents: &[u8]) -> snapshot::tmux::session::Session {
     send_keys(&project_name, &exec);
     wait_on(&completed);
 
-    let session = tmux::inspect(&project_name).unwrap();
+    let session = tmux::inspect(&Server::default(), &project_name).unwrap();
     cleanup(&project_name, &config_path);
     session
 }
@@ -75,11 +76,11 @@ fn open_muxed(project: &str, project_root: &Path) -> Result<(), String> {
 }
 
 fn kill_session(target: &str) {
-    let _ = load::tmux::call(&["kill-session", "-t", target]);
+    let _ = load::tmux::call(&Server::default(), &["kill-session", "-t", target]);
 }
 
 fn send_keys(target: &str, exec: &str) {
-    let _ = load::tmux::call(&["send-keys", "-t", target, exec, "KPEnter"]);
+    let _ = load::tmux::call(&Server::default(), &["send-keys", "-t", target, exec, "KPEnter"]);
 }
 
 fn wait_on(file: &PathBuf) {
@@ -4,7 +4,6 @@ extern crate common;
 extern crate snapshot;
 
 extern crate dirs;
-extern crate libc;
 extern crate load;
 extern crate rand;
 extern crate yaml_rust;
@@ -14,13 +13,17 @@ mod helpers;
 #[cfg(test)]
 mod test {
     mod load {
+        use common::args::Args;
         use common::rand_names;
+        use common::tmux::Server;
         use dirs::home_dir;
         use helpers::test_with_contents;
         use std::fs;
         use std::fs::File;
         use std::io::prelude::*;
         use std::path::PathBuf;
+        use std::thread::sleep;
+        use std::time::Duration;
 
         #[test]
         fn opens_3_windows_from_array() {
@@ -84,6 +87,20 @@ windows:
             assert_eq!(tests_window.panes.len(), 3)
         }
 
+        #[test]
+        fn window_names_with_punctuation_are_kept() {
+            let contents = b"---
+windows:
+  - my-app.rb:
+      panes: ['ls', 'ls']
+  - 'logs: dev'
+";
+            let session = test_with_contents(contents);
+            let window = session.find_window("my-app.rb").unwrap();
+            assert_eq!(window.panes.len(), 2);
+            assert!(session.find_window("logs: dev").is_some())
+        }
+
         #[test]
         fn window_with_empty_command_is_valid() {
             let contents = b"---
@@ -328,6 +345,29 @@ windows: ['ssh', 'git']
             let _ = fs::remove_file(&file);
         }
 
+        #[test]
+        fn panes_after_a_timed_out_wait_are_started() {
+            let file = rand_names::project_file_with_dir("/tmp");
+            let contents = format!(
+                "---
+windows:
+  - app:
+      panes:
+        - command: echo never
+          wait_for: {{ file: /no/such/file, timeout: 500ms }}
+        - command: touch {}
+          wait_for: {{ file: / }}
+          active: true
+",
+                file.display()
+            );
+
+            let session = test_with_contents(contents.as_bytes());
+            assert_eq!(session.windows[0].panes.len(), 2);
+            assert!(file.exists());
+            let _ = fs::remove_file(&file);
+        }
+
         #[test]
         fn expect_session_name_brians_session() {
             let contents = b"---
@@ -338,6 +378,130 @@ windows: ['ssh', 'git']
             assert_eq!(session.name, "Brians Session")
         }
 
+        #[test]
+        fn snapshot_loads_back_to_the_same_session() {
+            let root = PathBuf::from(format!("/tmp/{}", rand_names::project_file_name()));
+            let _ = fs::create_dir_all(root.join("src"));
+            let _ = fs::create_dir_all(root.join("log"));
+            let contents = format!(
+                "---
+root: {}
+windows:
+  - editor:
+      path: src
+      layout: even-horizontal
+      panes:
+        - ''
+        - path: .
+          active: true
+        - ''
+  - logs:
+      path: log
+      active: true
+      panes: ['']
+",
+                root.display()
+            );
+
+            let first = test_with_contents(contents.as_bytes()).to_yaml().unwrap();
+            let second = test_with_contents(first.as_bytes()).to_yaml().unwrap();
+            let _ = fs::remove_dir_all(&root);
+
+            assert!(first.contains(&format!("root: {}", root.display())));
+            assert!(first.contains("path: src"));
+            assert_eq!(first, second)
+        }
+
+        #[test]
+        fn snapshot_all_writes_a_file_per_session() {
+            let socket = rand_names::project_file_name();
+            let server = Server::new(None, Some(socket.clone()), None, None);
+            let projects = rand_names::project_path();
+            let snapshots = rand_names::project_path();
+            let _ = fs::create_dir(&projects);
+
+            for name in &["first", "second"] {
+                let mut file = File::create(projects.join(name).with_extension("yml")).unwrap();
+                let _ = file.write_all(b"windows: ['editor']");
+                let _ = load::exec(Args {
+                    arg_project: name.to_string(),
+                    flag_p: Some(projects.display().to_string()),
+                    flag_socket_name: Some(socket.clone()),
+                    ..Default::default()
+                });
+            }
+
+            let snapshot_all = |force: bool| {
+                snapshot::exec(Args {
+                    flag_all: true,
+                    arg_dir: snapshots.display().to_string(),
+                    flag_f: force,
+                    flag_socket_name: Some(socket.clone()),
+                    ..Default::default()
+                })
+            };
+
+            let first = snapshots.join("first.yml");
+            let _ = snapshot_all(false);
+            let written = fs::read_to_string(&first).unwrap();
+            let second_exists = snapshots.join("second.yml").exists();
+
+            let _ = fs::write(&first, "stale");
+            let _ = snapshot_all(false);
+            let skipped = fs::read_to_string(&first).unwrap();
+            let _ = snapshot_all(true);
+            let forced = fs::read_to_string(&first).unwrap();
+
+            let _ = load::tmux::call(&server, &["kill-server"]);
+            let _ = fs::remove_dir_all(&projects);
+            let _ = fs::remove_dir_all(&snapshots);
+
+            assert!(written.contains("editor"));
+            assert!(second_exists);
+            assert_eq!(skipped, "stale");
+            assert_eq!(forced, written)
+        }
+
+        #[test]
+        fn pane_contents_are_replayed() {
+            let socket = rand_names::project_file_name();
+            let server = Server::new(None, Some(socket.clone()), None, None);
+            let projects = rand_names::project_path();
+            let _ = fs::create_dir(&projects);
+
+            let saved = projects.join("0.0");
+            let _ = fs::write(&saved, "restored from a snapshot\n");
+            let project = format!(
+                "windows:\n  - editor:\n      panes:\n        - contents: {}\n",
+                saved.display()
+            );
+            let _ = fs::write(projects.join("replay.yml"), project);
+
+            let _ = load::exec(Args {
+                arg_project: "replay".to_string(),
+                flag_p: Some(projects.display().to_string()),
+                flag_socket_name: Some(socket.clone()),
+                flag_with_contents: true,
+                ..Default::default()
+            });
+
+            let mut shown = String::new();
+            for _ in 0..20 {
+                let output = load::tmux::call(&server, &["capture-pane", "-p", "-t", "replay"])
+                    .unwrap();
+                shown = String::from_utf8_lossy(&output.stdout).to_string();
+                if shown.contains("restored") {
+                    break;
+                };
+                sleep(Duration::from_millis(250));
+            }
+
+            let _ = load::tmux::call(&server, &["kill-server"]);
+            let _ = fs::remove_dir_all(&projects);
+
+            assert!(shown.contains("restored from a snapshot"))
+        }
+
         // TODO: Fix
         // This test should exist but we currently don't do anything to list panes.
         //    #[test]
@@ -2,7 +2,7 @@
 _muxed_completions()
 {
   if [ "$COMP_CWORD" -eq 1 ]; then
-    local commands="$(compgen -W "new edit snapshot" "${COMP_WORDS[1]}")"
+    local commands="$(compgen -W "new edit snapshot autosave restore import list pick recent last record run exec" "${COMP_WORDS[1]}")"
     local projects="$(compgen -W "$(echo $(ls ~/.muxed/))" "${COMP_WORDS[1]}")"
     COMPREPLY=( $commands $projects )
   elif [ "$COMP_CWORD" -eq 2 ]; then
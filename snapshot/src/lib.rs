@@ -1,21 +1,32 @@
 //! Muxedsnapshot. A tmux session cloner for Muxed.
 extern crate common;
 extern crate new;
-extern crate regex;
 extern crate serde;
 extern crate serde_yaml;
 
-mod capture;
+pub mod project;
 pub mod tmux;
 
 use common::args::Args;
 use common::first_run::check_first_run;
-use common::project_paths::project_paths;
+use common::project_paths::{project_paths, CONFIG_EXTENSION};
+use common::tmux::Server;
 use new::write_template as write_config;
+use std::fs;
+use std::path::{Path, PathBuf};
+use tmux::pane::contents::HISTORY_LINES;
+use tmux::pane::process::Filter;
+use tmux::session::Session;
+
+/// Pane contents are saved in a directory next to the project file, named
+/// after it. ex. `~/.muxed/my_project.contents/`
+static CONTENTS_EXTENSION: &str = "contents";
 
 /// The main execution method.
 /// Accepts two arguments. -n for the name of the project file and -t to target
-/// the session.
+/// the session. Without -t the session muxed is run from is used, and without
+/// a project name the file is named after the session. With --all every
+/// running session is written in to the given directory.
 ///
 /// # Examples
 ///
@@ -30,20 +41,136 @@ use new::write_template as write_config;
 /// ```
 /// $ ./muxed snapshot -n jasper -t 1
 /// ```
+///
+/// or
+///
+/// ```
+/// $ ./muxed snapshot --all ~/.muxed/
+/// ```
 pub fn exec(args: Args) -> Result<(), String> {
-    let session_name = &args.flag_t.as_ref().expect("No TMUX session running");
-    let project_paths = project_paths(&args);
+    let server = Server::from(&args);
+    let filter = Filter::new(args.flag_allow.as_deref(), args.flag_deny.as_deref());
+
+    if args.flag_all {
+        if args.arg_dir.is_empty() {
+            return Err("Snapshotting every session needs a directory to write them to, ex. --all ~/.muxed/".into());
+        };
 
+        let dir = Path::new(&args.arg_dir);
+        return snapshot_all(&server, &filter, dir, args.flag_f, history(&args));
+    };
+
+    let session_name = match args.flag_t.clone().or_else(|| tmux::current_session(&server)) {
+        Some(name) => name,
+        None => {
+            return Err("No session was given with -t and muxed isn't running inside of tmux.".into())
+        }
+    };
+
+    // Name the project after the session when no name was given.
+    let args = if args.arg_project.is_empty() {
+        Args {
+            arg_project: session_name.clone(),
+            ..args
+        }
+    } else {
+        args
+    };
+
+    let project_paths = project_paths(&args);
     check_first_run(&project_paths.project_directory)?;
 
-    let session = tmux::inspect(&session_name).unwrap();
-    let s = serde_yaml::to_string(&session).unwrap();
+    let contents_dir = project_paths.project_file.with_extension(CONTENTS_EXTENSION);
+    let contents = history(&args).map(|history| (contents_dir.as_path(), history));
 
-    write_config(s, &project_paths.project_file, args.flag_f).unwrap();
+    let session = snapshot(&server, &session_name, &filter, contents)?;
+    write_config(session.to_yaml()?, &project_paths.project_file, args.flag_f)?;
+    session.write_contents()?;
     println!("We made a snapshot of your session! \u{1F60A}");
     Ok(())
 }
 
+/// Write a project file for every running session in to `dir`. Files already
+/// matching their session are left alone, and existing files are only
+/// overwritten when forced.
+fn snapshot_all(
+    server: &Server,
+    filter: &Filter,
+    dir: &Path,
+    force: bool,
+    history: Option<usize>,
+) -> Result<(), String> {
+    check_first_run(dir)?;
+
+    for name in tmux::sessions(server)? {
+        let file = project_file(dir, &name);
+        let contents_dir = file.with_extension(CONTENTS_EXTENSION);
+        let contents = history.map(|history| (contents_dir.as_path(), history));
+
+        // Sessions can close while we work through the list.
+        let session = match snapshot(server, &name, filter, contents) {
+            Ok(session) => session,
+            Err(e) => {
+                println!("Skipped {}: {}", name, e);
+                continue;
+            }
+        };
+
+        let s = session.to_yaml()?;
+
+        if fs::read_to_string(&file).ok().as_ref() == Some(&s) {
+            session.write_contents()?;
+            println!("{} is up to date.", file.display());
+        } else if file.exists() && !force {
+            println!("Skipped {}: {} already exists, use -f to overwrite it.", name, file.display());
+        } else {
+            write_config(s, &file, force)?;
+            session.write_contents()?;
+            println!("We made a snapshot of {} in {} \u{1F60A}", name, file.display());
+        };
+    }
+
+    Ok(())
+}
+
+/// Inspect a session, ready to be written as a project file. Pane contents
+/// are captured when given a directory to save them in, and the lines of
+/// history to save.
+pub fn snapshot(
+    server: &Server,
+    name: &str,
+    filter: &Filter,
+    contents: Option<(&Path, usize)>,
+) -> Result<Session, String> {
+    let mut session = tmux::inspect(server, name)?;
+    session.filter_processes(filter);
+
+    if let Some((dir, history)) = contents {
+        session.capture_contents(server, dir, history)?;
+    };
+
+    Ok(session)
+}
+
+/// The lines of history to save with pane contents, when they're saved.
+fn history(args: &Args) -> Option<usize> {
+    if args.flag_with_contents {
+        Some(args.flag_history.unwrap_or(HISTORY_LINES))
+    } else {
+        None
+    }
+}
+
+/// The project file in `dir` a session is written to, named after it.
+pub fn project_file(dir: &Path, session: &str) -> PathBuf {
+    dir.join(project_name(session)).with_extension(CONFIG_EXTENSION)
+}
+
+/// The name of a session's project. `/` can't be in a file name.
+pub fn project_name(session: &str) -> String {
+    session.replace('/', "_")
+}
+
 #[cfg(test)]
 mod test {
     use common::rand_names;
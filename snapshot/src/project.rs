@@ -0,0 +1,226 @@
+//! A project file as muxed writes it. Snapshots fill one in from a running
+//! session and imports from another tool's project file. Settings that aren't
+//! needed are left out, and windows and panes are written in their short form
+//! when that's all they need.
+//! ex. A window with only a command is `- logs: tail -f log/development.log`
+use serde::ser::SerializeMap;
+use serde::{Serialize, Serializer};
+
+#[derive(Debug, Default, PartialEq, Serialize)]
+pub struct Project {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub root: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub socket_name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tmux_command: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tmux_config: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty", serialize_with = "commands")]
+    pub pre: Vec<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty", serialize_with = "commands")]
+    pub pre_window: Vec<String>,
+    pub windows: Vec<WindowEntry>,
+}
+
+/// A window in a project file, keyed by its name. A window with no panes runs
+/// its `command` in the window itself.
+#[derive(Debug, Default, PartialEq)]
+pub struct WindowEntry {
+    pub name: String,
+    pub command: Option<String>,
+    pub path: Option<String>,
+    pub layout: Option<String>,
+    pub active: bool,
+    pub panes: Vec<PaneEntry>,
+}
+
+#[derive(Serialize)]
+struct WindowDetail<'a> {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    path: &'a Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    layout: &'a Option<String>,
+    #[serde(skip_serializing_if = "is_false")]
+    active: bool,
+    panes: Vec<ShortPane<'a>>,
+}
+
+/// A pane in a project file. Panes opening in their window's directory that
+/// don't need focus are written as just their command.
+#[derive(Debug, Default, PartialEq, Serialize)]
+pub struct PaneEntry {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub command: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub contents: Option<String>,
+    #[serde(skip_serializing_if = "is_false")]
+    pub active: bool,
+}
+
+impl Project {
+    pub fn to_yaml(&self) -> Result<String, String> {
+        serde_yaml::to_string(self)
+            .map(|yaml| yaml + "\n")
+            .map_err(|e| format!("The project couldn't be written: {}", e))
+    }
+}
+
+impl WindowEntry {
+    fn is_short(&self) -> bool {
+        self.panes.is_empty() && self.path.is_none() && self.layout.is_none() && !self.active
+    }
+}
+
+impl PaneEntry {
+    fn is_short(&self) -> bool {
+        self.path.is_none() && self.contents.is_none() && !self.active
+    }
+}
+
+impl Serialize for WindowEntry {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut map = serializer.serialize_map(Some(1))?;
+
+        if self.is_short() {
+            map.serialize_entry(&self.name, &self.command.clone().unwrap_or_default())?;
+            return map.end();
+        };
+
+        // Windows with settings need panes, the command moves in to the one pane.
+        let lone = [PaneEntry {
+            command: self.command.clone(),
+            ..Default::default()
+        }];
+        let panes = if self.panes.is_empty() { &lone[..] } else { &self.panes[..] };
+
+        let detail = WindowDetail {
+            path: &self.path,
+            layout: &self.layout,
+            active: self.active,
+            panes: panes.iter().map(ShortPane).collect(),
+        };
+
+        map.serialize_entry(&self.name, &detail)?;
+        map.end()
+    }
+}
+
+/// A pane written as just its command when that's enough.
+struct ShortPane<'a>(&'a PaneEntry);
+
+impl<'a> Serialize for ShortPane<'a> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        if self.0.is_short() {
+            return serializer.serialize_str(self.0.command.as_ref().map_or("", |c| c.as_str()));
+        };
+
+        self.0.serialize(serializer)
+    }
+}
+
+/// A single command is written as a string, several as a list.
+fn commands<S>(commands: &[String], serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    match commands {
+        [command] => serializer.serialize_str(command),
+        _ => commands.serialize(serializer),
+    }
+}
+
+/// Used to leave `active: false` out of project files.
+pub fn is_false(b: &bool) -> bool {
+    !*b
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn window(name: &str, command: &str) -> WindowEntry {
+        WindowEntry {
+            name: name.to_string(),
+            command: Some(command.to_string()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn expect_short_form_for_plain_windows() {
+        let project = Project {
+            windows: vec![window("logs", "tail -f log/development.log")],
+            ..Default::default()
+        };
+
+        assert_eq!(
+            project.to_yaml().unwrap(),
+            "---\nwindows:\n  - logs: tail -f log/development.log\n"
+        )
+    }
+
+    #[test]
+    fn expect_command_to_move_in_to_a_pane_of_an_active_window() {
+        let project = Project {
+            windows: vec![WindowEntry {
+                active: true,
+                ..window("editor", "vim")
+            }],
+            ..Default::default()
+        };
+
+        assert_eq!(
+            project.to_yaml().unwrap(),
+            "---\nwindows:\n  - editor:\n      active: true\n      panes:\n        - vim\n"
+        )
+    }
+
+    #[test]
+    fn expect_short_panes_unless_they_need_more() {
+        let project = Project {
+            windows: vec![WindowEntry {
+                name: "editor".to_string(),
+                panes: vec![
+                    PaneEntry::default(),
+                    PaneEntry {
+                        command: Some("guard".to_string()),
+                        active: true,
+                        ..Default::default()
+                    },
+                ],
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+
+        assert_eq!(
+            project.to_yaml().unwrap(),
+            "---\nwindows:\n  - editor:\n      panes:\n        - \"\"\n        - command: guard\n          active: true\n"
+        )
+    }
+
+    #[test]
+    fn expect_one_command_as_a_string_and_more_as_a_list() {
+        let project = Project {
+            pre: vec!["docker-compose up -d".to_string()],
+            pre_window: vec!["nvm use".to_string(), "export PORT=3000".to_string()],
+            ..Default::default()
+        };
+
+        assert_eq!(
+            project.to_yaml().unwrap(),
+            "---\npre: docker-compose up -d\npre_window:\n  - nvm use\n  - export PORT=3000\nwindows: []\n"
+        )
+    }
+}
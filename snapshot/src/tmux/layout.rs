@@ -0,0 +1,93 @@
+//! Window layouts as tmux prints them with `#{window_layout}`.
+//! ex. `b05b,80x24,0,0[80x12,0,0,0,80x11,0,13,1]`
+//!
+//! Every cell is `{width}x{height},{x},{y}`, followed by the id of the pane in
+//! it, or by its children in `{}` or `[]`. The leading checksum covers the
+//! rest of the string.
+
+/// Drop the pane ids from a layout. Ids belong to the session the layout was
+/// read from and mean nothing to a new one. tmux accepts cells without them,
+/// handing out the panes in order, so the layout still applies with
+/// `select-layout`.
+pub fn without_pane_ids(layout: &str) -> String {
+    let body = match layout.find(',') {
+        Some(i) => &layout[i + 1..],
+        None => return layout.to_string(),
+    };
+
+    let mut stripped = String::new();
+    let mut word = String::new();
+    let mut pending = String::new();
+    // The numbers read since the last `{width}x{height}`. The fourth is an id.
+    let mut fields = 0;
+
+    for c in body.chars().chain(Some('\0')) {
+        if c.is_ascii_alphanumeric() {
+            word.push(c);
+            continue;
+        };
+
+        if !word.is_empty() {
+            fields = if word.contains('x') { 1 } else { fields + 1 };
+
+            if fields != 4 {
+                stripped.push_str(&pending);
+                stripped.push_str(&word);
+            };
+
+            pending.clear();
+            word.clear();
+        };
+
+        match c {
+            ',' => pending.push(c),
+            '\0' => (),
+            _ => {
+                stripped.push_str(&pending);
+                stripped.push(c);
+                pending.clear();
+            }
+        };
+    }
+
+    format!("{:04x},{}", checksum(&stripped), stripped)
+}
+
+/// The checksum tmux expects in front of a layout.
+fn checksum(layout: &str) -> u16 {
+    layout.bytes().fold(0u16, |sum, b| {
+        ((sum >> 1) | ((sum & 1) << 15)).wrapping_add(u16::from(b))
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn expect_checksum_to_match_tmux() {
+        let layout = "80x24,0,0[80x12,0,0,0,80x11,0,13{40x11,0,13,1,39x11,41,13,2}]";
+        assert_eq!(checksum(layout), 0xb05b)
+    }
+
+    #[test]
+    fn expect_ids_to_be_dropped_from_nested_cells() {
+        let layout = "0f05,80x24,0,0{40x24,0,0[40x12,0,0,0,40x11,0,13,1],39x24,41,0,2}";
+        assert_eq!(
+            without_pane_ids(layout),
+            "5181,80x24,0,0{40x24,0,0[40x12,0,0,40x11,0,13],39x24,41,0}"
+        )
+    }
+
+    #[test]
+    fn expect_id_to_be_dropped_from_single_pane() {
+        let layout = "b5be,173x42,0,0,1";
+        assert!(without_pane_ids(layout).ends_with(",173x42,0,0"))
+    }
+
+    #[test]
+    fn expect_layout_without_ids_to_stay_the_same() {
+        let layout = "5181,80x24,0,0{40x24,0,0[40x12,0,0,40x11,0,13],39x24,41,0}";
+        assert_eq!(without_pane_ids(layout), layout)
+    }
+}
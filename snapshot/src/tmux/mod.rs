@@ -1,32 +1,76 @@
+pub mod layout;
 pub mod pane;
 pub mod session;
 pub mod window;
 
+use common::tmux::Server;
 use self::pane::Pane;
 use self::session::Session;
 use self::window::Window;
+use std::env;
+use std::path::{Path, PathBuf};
 
-pub fn inspect(name: &str) -> Result<Session, String> {
-    let windows = match windows_for(name) {
+pub fn inspect(server: &Server, name: &str) -> Result<Session, String> {
+    let windows = match windows_for(server, name) {
         Ok(x) => x,
         Err(e) => return Err(e),
     };
 
     let windows = windows
         .into_iter()
-        .map(|w| Window::from_window(panes_for(name, &w).unwrap(), w))
+        .map(|w| Window::from_window(panes_for(server, name, &w).unwrap(), w))
         .collect();
 
     Ok(Session::new(name, windows))
 }
 
-fn windows_for(target: &str) -> Result<Vec<Window>, String> {
+/// The names of every session running on the server.
+pub fn sessions(server: &Server) -> Result<Vec<String>, String> {
+    let err = "\u{1F613} We couldn't list the running sessions";
+    let output = server
+        .command()
+        .args(["list-sessions", "-F", "#{session_name}"])
+        .output()
+        .map_err(|e| format!("{} - {}", err, e))?;
+
+    if !output.status.success() {
+        return Err(format!("{} - {}", err, String::from_utf8_lossy(&output.stderr).trim()));
+    };
+
+    let sessions = String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .map(|line| line.to_string())
+        .collect();
+
+    Ok(sessions)
+}
+
+/// The session muxed was run from, when it's run inside of tmux.
+pub fn current_session(server: &Server) -> Option<String> {
+    env::var_os("TMUX")?;
+
+    let output = server
+        .command()
+        .args(["display-message", "-p", "#S"])
+        .output()
+        .ok()?;
+
+    let name = String::from_utf8_lossy(&output.stdout).trim_end_matches('\n').to_string();
+
+    if !output.status.success() || name.is_empty() {
+        return None;
+    };
+
+    Some(name)
+}
+
+fn windows_for(server: &Server, target: &str) -> Result<Vec<Window>, String> {
     let err = format!("\u{1F613} The session {} was not found.", target);
-    let output = Window::window_list(target).map_err(|e| format!("{} - {}", err, e))?;
+    let output = Window::window_list(server, target).map_err(|e| format!("{} - {}", err, e))?;
 
     let windows = String::from_utf8_lossy(&output.stdout)
         .lines()
-        .filter_map(|line| Window::from_line(line))
+        .filter_map(Window::from_line)
         .collect::<Vec<_>>();
 
     if windows.is_empty() {
@@ -36,15 +80,84 @@ fn windows_for(target: &str) -> Result<Vec<Window>, String> {
     Ok(windows)
 }
 
-fn panes_for(session_name: &str, w: &Window) -> Result<Vec<Pane>, String> {
-    let target = format!("{}:{}", &session_name, &w.name);
-    let output = Pane::pane_list(&target)
-        .map_err(|e| format!("We couldn't find panes for the {} window - {}", &target, e))?;
+fn panes_for(server: &Server, session_name: &str, w: &Window) -> Result<Vec<Pane>, String> {
+    let output = Pane::pane_list(server, &w.id).map_err(|e| {
+        format!("We couldn't find panes for the {}:{} window - {}", &session_name, &w.name, e)
+    })?;
 
     let panes = String::from_utf8_lossy(&output.stdout)
         .lines()
-        .filter_map(|line| Pane::from_line(line))
+        .filter_map(Pane::from_line)
         .collect::<Vec<_>>();
 
     Ok(panes)
 }
+
+/// The deepest directory containing every path.
+pub fn common_root<'a, I>(paths: I) -> Option<PathBuf>
+where
+    I: IntoIterator<Item = &'a Path>,
+{
+    let mut paths = paths.into_iter();
+    let mut root = paths.next()?.to_path_buf();
+
+    for path in paths {
+        while !path.starts_with(&root) {
+            if !root.pop() {
+                return None;
+            };
+        }
+    }
+
+    Some(root)
+}
+
+/// A path as written in a project file, relative to the project root when
+/// it's below it.
+pub fn relative_path(path: &Path, root: &Path) -> String {
+    if path == root {
+        return ".".to_string();
+    };
+
+    path.strip_prefix(root).unwrap_or(path).display().to_string()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn expect_common_root_of_siblings() {
+        let paths = vec![Path::new("/code/muxed/load"), Path::new("/code/muxed/snapshot/src")];
+        assert_eq!(common_root(paths), Some(PathBuf::from("/code/muxed")))
+    }
+
+    #[test]
+    fn expect_common_root_of_a_single_path() {
+        let paths = vec![Path::new("/code/muxed")];
+        assert_eq!(common_root(paths), Some(PathBuf::from("/code/muxed")))
+    }
+
+    #[test]
+    fn expect_common_root_to_stop_at_whole_components() {
+        let paths = vec![Path::new("/code/muxed"), Path::new("/code/muxed-old")];
+        assert_eq!(common_root(paths), Some(PathBuf::from("/code")))
+    }
+
+    #[test]
+    fn expect_dot_for_the_root() {
+        assert_eq!(relative_path(Path::new("/code"), Path::new("/code")), ".")
+    }
+
+    #[test]
+    fn expect_relative_path_below_the_root() {
+        let path = relative_path(Path::new("/code/muxed/load"), Path::new("/code"));
+        assert_eq!(path, "muxed/load")
+    }
+
+    #[test]
+    fn expect_absolute_path_outside_the_root() {
+        let path = relative_path(Path::new("/var/log"), Path::new("/code"));
+        assert_eq!(path, "/var/log")
+    }
+}
@@ -0,0 +1,75 @@
+use common::tmux::Server;
+use std::fs;
+use std::path::PathBuf;
+
+/// How many lines of history are saved with each pane, unless `--history`
+/// says otherwise.
+pub static HISTORY_LINES: usize = 2000;
+
+/// What a pane is showing, and the file it's saved to next to the project.
+/// `file`: Where the contents are written. Referenced from the project file.
+/// `text`: The visible pane and its history, escape sequences included.
+#[derive(Clone, Debug)]
+pub struct Contents {
+    pub file: PathBuf,
+    pub text: String,
+}
+
+impl Contents {
+    /// Capture the pane with `capture-pane`, `history` lines back. Trailing
+    /// blank lines are the unused part of the screen and are dropped.
+    pub fn capture(server: &Server, pane_id: &str, file: PathBuf, history: usize) -> Result<Contents, String> {
+        let start = format!("-{}", history);
+        let output = server
+            .command()
+            .args(["capture-pane", "-p", "-e", "-S", &start, "-t", pane_id])
+            .output()
+            .map_err(|e| format!("We couldn't capture the pane {} - {}", pane_id, e))?;
+
+        if !output.status.success() {
+            return Err(format!(
+                "We couldn't capture the pane {} - {}",
+                pane_id,
+                String::from_utf8_lossy(&output.stderr).trim()
+            ));
+        };
+
+        let text = trim_blank_lines(&String::from_utf8_lossy(&output.stdout));
+        Ok(Contents { file, text })
+    }
+
+    pub fn write(&self) -> Result<(), String> {
+        if let Some(dir) = self.file.parent() {
+            fs::create_dir_all(dir)
+                .map_err(|e| format!("Could not create the directory {}. Error: {}", dir.display(), e))?;
+        };
+
+        fs::write(&self.file, &self.text)
+            .map_err(|e| format!("Could not write the file {}. Error: {}", self.file.display(), e))
+    }
+}
+
+fn trim_blank_lines(text: &str) -> String {
+    let trimmed = text.trim_end_matches(&['\n', ' '][..]);
+
+    if trimmed.is_empty() {
+        return String::new();
+    };
+
+    format!("{}\n", trimmed)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn expect_blank_lines_to_be_dropped() {
+        assert_eq!(trim_blank_lines("$ ls\nCargo.toml\n$ \n\n\n"), "$ ls\nCargo.toml\n$\n")
+    }
+
+    #[test]
+    fn expect_empty_pane_to_be_empty() {
+        assert_eq!(trim_blank_lines("\n\n\n"), "")
+    }
+}
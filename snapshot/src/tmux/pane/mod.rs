@@ -1,82 +1,101 @@
-//use std::path::PathBuf;
-//use tmux::window::Window;
-use capture::retrieve_capture;
-use serde::{Deserialize, Serialize, Serializer};
+use common::tmux::Server;
+use project::PaneEntry;
+use serde::Deserialize;
 use std::io;
-use std::path::PathBuf;
-use std::process::{Command, Output};
+use std::path::{Path, PathBuf};
+use tmux::relative_path;
+use std::process::Output;
+use std::str::FromStr;
 
+pub mod contents;
 pub mod pid;
 pub mod process;
 
+use self::contents::Contents;
 use self::pid::Pid;
 use self::process::Process;
 
-static ACTIVE_REGEX: &str = r"\s(\(active\))";
-static PATH_REGEX: &str = r"\(Path: (.*)\) \(PID";
-static PID_REGEX: &str = r"\(PID: ([0-9]*)\)";
-
-// Example format: "1: [123x14] (Path: /muxed/muxedsnapshot) (PID: 22541) (active)"
-static LIST_FORMAT: &str = "'#{pane_index}: [#{pane_width}x#{pane_height}] (Path: #{pane_current_path}) (PID: #{pane_pid}) #{?pane_active,(active), } '";
+// Fields are tab separated. The path comes last so any tabs inside of it
+// survive the split.
+// Example output: "%3\t1\t1\t123\t14\t22541\tvim\t/muxed/muxedsnapshot"
+static LIST_FORMAT: &str = "#{pane_id}\t#{pane_index}\t#{pane_active}\t#{pane_width}\t#{pane_height}\t#{pane_pid}\t#{pane_current_command}\t#{pane_current_path}";
 
+/// A pane as reported by tmux.
+/// `command`: The name of the foreground program according to tmux.
+/// `process`: The full command running in the pane, if any.
+/// `contents`: What the pane shows, when it's been captured.
 #[derive(Clone, Debug, Deserialize)]
 pub struct Pane {
+    pub id: String,
+    pub index: usize,
     pub active: bool,
+    pub width: usize,
+    pub height: usize,
+    pub pid: Pid,
+    pub command: String,
     pub path: PathBuf,
     pub process: Option<Process>,
+    #[serde(skip)]
+    pub contents: Option<Contents>,
 }
 
 impl Pane {
-    pub fn new(active: bool, path: PathBuf, process: Option<Process>) -> Pane {
-        Pane {
+    pub fn from_line(line: &str) -> Option<Pane> {
+        let mut fields = line.splitn(8, '\t');
+
+        let id = fields.next().filter(|id| id.starts_with('%'))?.to_string();
+        let index = usize::from_str(fields.next()?).ok()?;
+        let active = fields.next()? == "1";
+        let width = usize::from_str(fields.next()?).ok()?;
+        let height = usize::from_str(fields.next()?).ok()?;
+        let pid = fields.next().filter(|p| i32::from_str(p).is_ok()).map(Pid::new)?;
+        let command = fields.next()?.to_string();
+        let path = PathBuf::from(fields.next()?);
+
+        let process = Process::foreground(&pid);
+
+        Some(Pane {
+            id,
+            index,
             active,
+            width,
+            height,
+            pid,
+            command,
             path,
             process,
-        }
+            contents: None,
+        })
     }
 
-    pub fn from_line(line: &str) -> Option<Pane> {
-        let active = retrieve_capture(line, ACTIVE_REGEX).is_some();
-
-        let path = match retrieve_capture(line, PATH_REGEX) {
-            Some(x) => PathBuf::from(x),
-            None => return None,
-        };
-
-        let pid = match retrieve_capture(line, PID_REGEX) {
-            Some(x) => Pid::new(x),
-            None => return None,
-        };
+    /// The pane as written to a project file. `window_path` is where the
+    /// window opens, so panes there don't repeat it.
+    pub fn entry(&self, window_path: &Path, root: &Path, focused: bool) -> PaneEntry {
+        let command = self.process.as_ref().map(|p| p.process.clone());
+        let contents = self.contents.as_ref().map(|c| c.file.display().to_string());
 
-        let process = match Process::process_string_from(pid) {
-            Ok(x) => Some(Process::new(x)),
-            Err(_) => None,
+        let path = if self.path == window_path {
+            None
+        } else {
+            Some(relative_path(&self.path, root))
         };
 
-        Some(Pane::new(active, path, process))
+        PaneEntry {
+            command,
+            path,
+            contents,
+            active: focused,
+        }
     }
 
-    pub fn pane_list(target: &str) -> Result<Output, io::Error> {
-        Command::new("tmux")
+    pub fn pane_list(server: &Server, target: &str) -> Result<Output, io::Error> {
+        server
+            .command()
             .args(&["list-panes", "-t", target, "-F", LIST_FORMAT])
             .output()
     }
 }
 
-impl Serialize for Pane {
-    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
-    where
-        S: Serializer,
-    {
-        let process_str = match self.process.clone() {
-            Some(process) => process.process,
-            None => "".to_string(),
-        };
-
-        serializer.serialize_str(process_str.as_str())
-    }
-}
-
 #[cfg(test)]
 mod test {
     use super::*;
@@ -84,22 +103,74 @@ mod test {
 
     #[test]
     fn expect_active_to_be_false() {
-        let line = "2: [123x14] (Path: /muxed-project/muxed) (PID: 22541)  ";
+        let line = "%3\t2\t0\t123\t14\t22541\tbash\t/muxed-project/muxed";
         let pane = Pane::from_line(line).unwrap();
         assert!(!pane.active)
     }
 
     #[test]
     fn expect_active_to_be_true() {
-        let line = "1: [123x14] (Path: /muxed-project/muxed) (PID: 22541) (active)";
+        let line = "%3\t1\t1\t123\t14\t22541\tbash\t/muxed-project/muxed";
         let pane = Pane::from_line(line).unwrap();
         assert!(pane.active)
     }
 
     #[test]
     fn expect_pane_to_match() {
-        let line = "1: [123x14] (Path: /muxed/muxedsnapshot) (PID: 22541) (active)";
+        let line = "%3\t1\t1\t123\t14\t22541\tbash\t/muxed/muxedsnapshot";
         let pane = Pane::from_line(line).unwrap();
         assert_eq!(pane.path, PathBuf::from("/muxed/muxedsnapshot"));
     }
+
+    #[test]
+    fn expect_path_with_spaces_and_tabs() {
+        let line = "%3\t1\t1\t123\t14\t22541\tbash\t/tmp/Directory With\tSpaces";
+        let pane = Pane::from_line(line).unwrap();
+        assert_eq!(pane.path, PathBuf::from("/tmp/Directory With\tSpaces"));
+    }
+
+    #[test]
+    fn expect_id_index_and_size() {
+        let line = "%3\t1\t1\t123\t14\t22541\tvim\t/muxed";
+        let pane = Pane::from_line(line).unwrap();
+        assert_eq!(pane.id, "%3");
+        assert_eq!(pane.index, 1);
+        assert_eq!((pane.width, pane.height), (123, 14));
+        assert_eq!(pane.pid.pid, 22541);
+        assert_eq!(pane.command, "vim")
+    }
+
+    #[test]
+    fn expect_none_with_a_bad_pid() {
+        let line = "%3\t1\t1\t123\t14\tabc\tvim\t/muxed";
+        assert!(Pane::from_line(line).is_none())
+    }
+
+    #[test]
+    fn expect_none_from_old_format() {
+        let line = "1: [123x14] (Path: /muxed/muxedsnapshot) (PID: 22541) (active)";
+        assert!(Pane::from_line(line).is_none())
+    }
+
+    #[test]
+    fn expect_entry_to_be_the_command_in_the_window_path() {
+        let line = "%3\t1\t0\t123\t14\t22541\tbash\t/muxed/load";
+        let pane = Pane::from_line(line).unwrap();
+        let entry = pane.entry(Path::new("/muxed/load"), Path::new("/muxed"), false);
+        assert_eq!(entry, PaneEntry::default())
+    }
+
+    #[test]
+    fn expect_entry_with_relative_path_and_focus() {
+        let line = "%3\t1\t1\t123\t14\t22541\tbash\t/muxed/snapshot";
+        let pane = Pane::from_line(line).unwrap();
+        let entry = pane.entry(Path::new("/muxed/load"), Path::new("/muxed"), true);
+        let expected = PaneEntry {
+            command: None,
+            path: Some("snapshot".to_string()),
+            contents: None,
+            active: true,
+        };
+        assert_eq!(entry, expected)
+    }
 }
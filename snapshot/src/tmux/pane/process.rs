@@ -1,11 +1,28 @@
+use common::shell::shell_quote;
 use serde::{Deserialize, Serialize};
 use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
 use std::process::Command;
+use std::str::FromStr;
 use tmux::pane::pid::Pid;
 
+static PROC: &str = "/proc";
+
+/// Commands that aren't worth restoring by default. Shells are what tmux
+/// starts in a new pane anyway, and `sudo`/`su` would only prompt for a
+/// password on load.
+static DEFAULT_DENY: &[&str] = &[
+    "bash", "csh", "dash", "fish", "ksh", "sh", "su", "sudo", "tcsh", "zsh",
+];
+
+/// The command running in the foreground of a pane.
+/// `process`: The command line, quoted so a shell can run it again.
+/// `argv`: The arguments the process was started with.
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Process {
     pub process: String,
+    pub argv: Vec<String>,
 }
 
 impl Process {
@@ -13,13 +30,65 @@ impl Process {
     where
         S: Into<String>,
     {
-        Process {
-            process: process.into(),
-        }
+        let process = process.into();
+        let argv = process.split_whitespace().map(|a| a.to_string()).collect();
+
+        Process { process, argv }
+    }
+
+    pub fn from_argv(argv: Vec<String>) -> Process {
+        let process = argv
+            .iter()
+            .map(|a| shell_quote(a))
+            .collect::<Vec<String>>()
+            .join(" ");
+
+        Process { process, argv }
+    }
+
+    /// The name of the program, without its path. Login shells are started
+    /// as `-bash` so the dash is dropped too.
+    pub fn program(&self) -> &str {
+        let first = self.argv.first().map_or("", |a| a.as_str());
+        let name = first.rsplit('/').next().unwrap_or(first);
+        name.trim_start_matches('-')
+    }
+
+    /// Find the command in the foreground of the pane whose shell has the
+    /// given pid. On systems with `/proc` the terminal's foreground process
+    /// group is used to pick the process and its full argv is read. Elsewhere
+    /// we fall back on `pgrep`.
+    pub fn foreground(pid: &Pid) -> Option<Process> {
+        let proc_dir = Path::new(PROC).join(pid.to_string());
+
+        if !proc_dir.exists() {
+            return Process::process_string_from(pid.clone()).ok().map(Process::new);
+        };
+
+        let shell = Stat::read(pid.pid)?;
+
+        // The shell itself is in the foreground, there's nothing to restore.
+        if shell.tpgid <= 0 || shell.tpgid == shell.pgrp {
+            return None;
+        };
+
+        let descendants = descendants(pid.pid);
+        let leader = descendants
+            .iter()
+            .find(|&&p| p == shell.tpgid)
+            .cloned()
+            .or_else(|| {
+                descendants
+                    .iter()
+                    .find(|&&p| Stat::read(p).map(|s| s.pgrp == shell.tpgid).unwrap_or(false))
+                    .cloned()
+            })?;
+
+        let argv = cmdline(leader)?;
+        Some(Process::from_argv(argv))
     }
 
     pub fn process_string_from(pid: Pid) -> Result<String, String> {
-        // we could cat /proc/pid/cmdline instead of calling pgrep
         let output = Command::new("pgrep")
             .arg("-lf")
             .arg("-P")
@@ -54,6 +123,162 @@ impl fmt::Display for Process {
     }
 }
 
+/// Decides which commands are worth restoring. A command is restored when its
+/// program isn't denied, and, if anything is allowed explicitly, it's allowed.
+/// `allow`: Program names to restore. Empty means everything not denied.
+/// `deny`: Program names never to restore.
+#[derive(Clone, Debug)]
+pub struct Filter {
+    pub allow: Vec<String>,
+    pub deny: Vec<String>,
+}
+
+impl Filter {
+    /// Build a filter from comma separated lists of program names. Denied
+    /// programs are added to the default deny list.
+    pub fn new(allow: Option<&str>, deny: Option<&str>) -> Filter {
+        let list = |l: Option<&str>| -> Vec<String> {
+            l.map_or(vec![], |l| {
+                l.split(',')
+                    .map(|p| p.trim().to_string())
+                    .filter(|p| !p.is_empty())
+                    .collect()
+            })
+        };
+
+        let mut default_deny: Vec<String> = DEFAULT_DENY.iter().map(|p| p.to_string()).collect();
+        default_deny.append(&mut list(deny));
+
+        Filter {
+            allow: list(allow),
+            deny: default_deny,
+        }
+    }
+
+    pub fn allows(&self, process: &Process) -> bool {
+        let program = process.program();
+
+        if program.is_empty() || self.deny.iter().any(|p| p == program) {
+            return false;
+        };
+
+        self.allow.is_empty() || self.allow.iter().any(|p| p == program)
+    }
+}
+
+impl Default for Filter {
+    fn default() -> Self {
+        Filter::new(None, None)
+    }
+}
+
+/// The fields of `/proc/{pid}/stat` we need.
+/// `ppid`: The parent process.
+/// `pgrp`: The process group.
+/// `tpgid`: The foreground process group of the process's terminal.
+#[derive(Debug, PartialEq)]
+struct Stat {
+    ppid: i32,
+    pgrp: i32,
+    tpgid: i32,
+}
+
+impl Stat {
+    fn read(pid: i32) -> Option<Stat> {
+        let stat = fs::read_to_string(Path::new(PROC).join(pid.to_string()).join("stat")).ok()?;
+        Stat::parse(&stat)
+    }
+
+    /// The process name is wrapped in parens and may contain spaces or parens
+    /// itself, so the fields are read from after the last `)`.
+    /// ex. `1234 (my cmd) S 1 1234 1234 34816 5678 ...`
+    fn parse(stat: &str) -> Option<Stat> {
+        let fields: Vec<&str> = stat[stat.rfind(')')? + 1..].split_whitespace().collect();
+        let field = |i: usize| fields.get(i).and_then(|f| i32::from_str(f).ok());
+
+        Some(Stat {
+            ppid: field(1)?,
+            pgrp: field(2)?,
+            tpgid: field(5)?,
+        })
+    }
+}
+
+/// All the processes below `pid`, closest first.
+fn descendants(pid: i32) -> Vec<i32> {
+    let mut found = vec![];
+    let mut queue = vec![pid];
+
+    while !queue.is_empty() {
+        let parent = queue.remove(0);
+        let kids = children(parent);
+        found.extend(kids.iter().cloned());
+        queue.extend(kids);
+    }
+
+    found
+}
+
+/// The children of a process from `/proc/{pid}/task/*/children`. Kernels
+/// built without that file get every process scanned for its parent instead.
+fn children(pid: i32) -> Vec<i32> {
+    let tasks = Path::new(PROC).join(pid.to_string()).join("task");
+    let listed: Option<Vec<i32>> = fs::read_dir(&tasks).ok().and_then(|entries| {
+        let mut kids = vec![];
+
+        for entry in entries.filter_map(|e| e.ok()) {
+            let listing = fs::read_to_string(entry.path().join("children")).ok()?;
+            kids.extend(listing.split_whitespace().filter_map(|p| i32::from_str(p).ok()));
+        }
+
+        Some(kids)
+    });
+
+    match listed {
+        Some(kids) => kids,
+        None => scan_children(pid),
+    }
+}
+
+fn scan_children(pid: i32) -> Vec<i32> {
+    let entries = match fs::read_dir(PROC) {
+        Ok(entries) => entries,
+        Err(_) => return vec![],
+    };
+
+    let mut kids: Vec<i32> = entries
+        .filter_map(|e| e.ok())
+        .filter_map(|e| e.file_name().to_str().and_then(|p| i32::from_str(p).ok()))
+        .filter(|&p| Stat::read(p).map(|s| s.ppid == pid).unwrap_or(false))
+        .collect();
+
+    kids.sort();
+    kids
+}
+
+/// Read the NUL separated argv of a process.
+fn cmdline(pid: i32) -> Option<Vec<String>> {
+    let path: PathBuf = Path::new(PROC).join(pid.to_string()).join("cmdline");
+    let raw = fs::read(path).ok()?;
+
+    let argv: Vec<String> = raw
+        .split(|b| *b == 0)
+        .map(|a| String::from_utf8_lossy(a).to_string())
+        .collect();
+
+    // The list ends with a NUL, leaving an empty last element.
+    let argv: Vec<String> = match argv.split_last() {
+        Some((last, rest)) if last.is_empty() => rest.to_vec(),
+        _ => argv,
+    };
+
+    if argv.is_empty() {
+        return None;
+    };
+
+    Some(argv)
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -64,4 +289,81 @@ mod test {
         let process = Process::strip_pid(line);
         assert_eq!(process, "vim .")
     }
+
+    #[test]
+    fn expect_bare_args_to_stay_bare() {
+        let argv = vec!["tail".to_string(), "-f".to_string(), "log/development.log".to_string()];
+        let process = Process::from_argv(argv);
+        assert_eq!(process.process, "tail -f log/development.log")
+    }
+
+    #[test]
+    fn expect_args_with_spaces_to_be_quoted() {
+        let argv = vec!["sh".to_string(), "-c".to_string(), "sleep 10; echo it's done".to_string()];
+        let process = Process::from_argv(argv);
+        assert_eq!(process.process, "sh -c 'sleep 10; echo it'\\''s done'")
+    }
+
+    #[test]
+    fn expect_program_without_path_or_login_dash() {
+        assert_eq!(Process::new("/usr/bin/vim .").program(), "vim");
+        assert_eq!(Process::new("-bash").program(), "bash")
+    }
+
+    #[test]
+    fn expect_stat_with_parens_in_name() {
+        let stat = "1234 (my (odd) cmd) S 1 1234 1234 34816 5678 4194560 0 0";
+        let expected = Stat {
+            ppid: 1,
+            pgrp: 1234,
+            tpgid: 5678,
+        };
+        assert_eq!(Stat::parse(stat), Some(expected))
+    }
+
+    #[test]
+    fn expect_none_from_short_stat() {
+        assert_eq!(Stat::parse("1234 (cmd) S 1"), None)
+    }
+
+    #[test]
+    fn expect_shells_to_be_denied_by_default() {
+        let filter = Filter::default();
+        assert!(!filter.allows(&Process::new("zsh")));
+        assert!(filter.allows(&Process::new("vim .")))
+    }
+
+    #[test]
+    fn expect_only_allowed_programs() {
+        let filter = Filter::new(Some("vim, tail"), None);
+        assert!(filter.allows(&Process::new("tail -f log")));
+        assert!(!filter.allows(&Process::new("top")))
+    }
+
+    #[test]
+    fn expect_denied_programs_to_be_added() {
+        let filter = Filter::new(None, Some("htop"));
+        assert!(!filter.allows(&Process::new("htop")));
+        assert!(!filter.allows(&Process::new("bash")))
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn expect_children_of_this_process() {
+        let mut child = Command::new("sleep").arg("5").spawn().unwrap();
+        let kids = children(std::process::id() as i32);
+        let _ = child.kill();
+        let _ = child.wait();
+
+        assert!(kids.contains(&(child.id() as i32)))
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn expect_cmdline_of_this_process() {
+        let expected: Vec<String> = std::env::args().collect();
+        let argv = cmdline(std::process::id() as i32);
+
+        assert_eq!(argv, Some(expected))
+    }
 }
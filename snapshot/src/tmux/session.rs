@@ -1,7 +1,13 @@
-use serde::{Deserialize, Serialize};
+use common::tmux::Server;
+use project::Project;
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+use tmux::common_root;
+use tmux::pane::contents::Contents;
+use tmux::pane::process::Filter;
 use tmux::window::Window;
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Deserialize)]
 pub struct Session {
     pub name: String,
     pub windows: Vec<Window>,
@@ -21,4 +27,61 @@ impl Session {
     pub fn find_window(&self, name: &str) -> Option<&Window> {
         self.windows.iter().find(|&w| w.name == name)
     }
+
+    /// The deepest directory holding every pane, used as the project root.
+    pub fn root(&self) -> PathBuf {
+        let paths = self
+            .windows
+            .iter()
+            .flat_map(|w| w.panes.iter())
+            .map(|p| p.path.as_path());
+
+        common_root(paths).unwrap_or_else(|| PathBuf::from("/"))
+    }
+
+    /// The session as a project muxed can load.
+    pub fn project(&self) -> Project {
+        let root = self.root();
+
+        Project {
+            root: Some(root.display().to_string()),
+            windows: self.windows.iter().map(|w| w.entry(&root)).collect(),
+            ..Default::default()
+        }
+    }
+
+    /// The session as a project file muxed can load.
+    pub fn to_yaml(&self) -> Result<String, String> {
+        self.project().to_yaml()
+    }
+
+    /// Capture what every pane shows, with `history` lines of scrollback.
+    /// Each pane is saved to `dir` in a file named after the position of its
+    /// window and its own, ex. `0.1`.
+    pub fn capture_contents(&mut self, server: &Server, dir: &Path, history: usize) -> Result<(), String> {
+        for (w, window) in self.windows.iter_mut().enumerate() {
+            for (p, pane) in window.panes.iter_mut().enumerate() {
+                let file = dir.join(format!("{}.{}", w, p));
+                pane.contents = Some(Contents::capture(server, &pane.id, file, history)?);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Write the captured pane contents to their files.
+    pub fn write_contents(&self) -> Result<(), String> {
+        self.windows
+            .iter()
+            .flat_map(|w| w.panes.iter())
+            .filter_map(|p| p.contents.as_ref())
+            .try_for_each(|c| c.write())
+    }
+
+    /// Forget the commands running in panes that aren't worth restoring.
+    pub fn filter_processes(&mut self, filter: &Filter) {
+        for pane in self.windows.iter_mut().flat_map(|w| w.panes.iter_mut()) {
+            pane.process = pane.process.take().filter(|p| filter.allows(p));
+        }
+    }
 }
@@ -1,40 +1,37 @@
-use capture::retrieve_capture;
-use serde::{Deserialize, Serialize, Serializer};
-use serde::ser::SerializeMap;
+use common::tmux::Server;
+use project::WindowEntry;
+use serde::Deserialize;
 use std::io;
-use std::process::{Command, Output};
+use std::path::Path;
+use std::process::Output;
+use std::str::FromStr;
+use tmux::layout::without_pane_ids;
 use tmux::pane::Pane;
+use tmux::relative_path;
 
-// Come back and question the accuracy of windows without names
-// that have active, or previous window designations.
-static NAME_REGEX: &str = r":\s(\w*)[$\*-]?\s+\(";
-static ACTIVE_REGEX: &str = r"\s.*(\*)\s";
-static LAYOUT_REGEX: &str = r"\s\[layout\s(.*)\]";
-
-// Example format: "2: vim* (1 panes) [layout b5be,173x42,0,0,1]"
-static LIST_FORMAT: &str = "'#{window_index}: #{window_name}#{?window_active,*, } (#{window_panes} panes) [layout #{window_layout}]'";
+// Fields are tab separated. The name comes last so any tabs inside of it
+// survive the split.
+// Example output: "@2\t2\t1\tb5be,173x42,0,0,1\tvim"
+static LIST_FORMAT: &str = "#{window_id}\t#{window_index}\t#{window_active}\t#{window_layout}\t#{window_name}";
 
 #[derive(Debug, Deserialize)]
 pub struct Window {
+    pub id: String,
+    pub index: usize,
     pub active: bool,
     pub layout: String,
     pub name: String,
     pub panes: Vec<Pane>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
-pub struct WindowInner {
-    pub active: bool,
-    pub layout: String,
-    pub panes: Vec<Pane>,
-}
-
 impl Window {
-    pub fn new<S>(active: bool, layout: S, name: S, panes: Vec<Pane>) -> Window
+    pub fn new<S>(id: S, index: usize, active: bool, layout: S, name: S, panes: Vec<Pane>) -> Window
     where
         S: Into<String>,
     {
         Window {
+            id: id.into(),
+            index,
             active,
             layout: layout.into(),
             name: name.into(),
@@ -43,46 +40,55 @@ impl Window {
     }
 
     pub fn from_window(panes: Vec<Pane>, w: Window) -> Window {
-        Window::new(w.active, w.layout, w.name, panes)
+        Window::new(w.id, w.index, w.active, w.layout, w.name, panes)
     }
 
     pub fn from_line(line: &str) -> Option<Window> {
-        let active = retrieve_capture(line, ACTIVE_REGEX).is_some();
+        let mut fields = line.splitn(5, '\t');
 
-        let layout = match retrieve_capture(line, LAYOUT_REGEX) {
-            Some(x) => x,
-            None => return None,
-        };
+        let id = fields.next().filter(|id| id.starts_with('@'))?;
+        let index = usize::from_str(fields.next()?).ok()?;
+        let active = fields.next()? == "1";
+        let layout = fields.next()?;
+        let name = fields.next()?;
 
-        let name = match retrieve_capture(line, NAME_REGEX) {
-            Some(x) => x,
-            None => return None,
-        };
-
-        Some(Window::new(active, layout, name, vec![]))
+        Some(Window::new(id, index, active, layout, name, vec![]))
     }
 
-    pub fn window_list(target: &str) -> Result<Output, io::Error> {
-        Command::new("tmux")
-            .args(&["list-windows", "-t", target, "-F", LIST_FORMAT])
-            .output()
+    /// Where the window opens, the directory of its first pane.
+    pub fn path(&self) -> Option<&Path> {
+        self.panes.first().map(|p| p.path.as_path())
     }
-}
 
-impl Serialize for Window {
-    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
-    where
-        S: Serializer,
-    {
-        let window = WindowInner {
+    /// The window as written to a project file with paths relative to `root`.
+    pub fn entry(&self, root: &Path) -> WindowEntry {
+        let window_path = self.path().unwrap_or(root);
+        // A lone pane is focused anyway, only mark panes when there's a choice.
+        let choice = self.panes.len() > 1;
+
+        WindowEntry {
+            name: self.name.clone(),
+            command: None,
+            path: if window_path == root {
+                None
+            } else {
+                Some(relative_path(window_path, root))
+            },
+            layout: Some(without_pane_ids(&self.layout)),
             active: self.active,
-            layout: self.layout.clone(),
-            panes: self.panes.clone(),
-        };
+            panes: self
+                .panes
+                .iter()
+                .map(|p| p.entry(window_path, root, choice && p.active))
+                .collect(),
+        }
+    }
 
-        let mut map = serializer.serialize_map(Some(1))?;
-        map.serialize_entry(&self.name, &window)?;
-        map.end()
+    pub fn window_list(server: &Server, target: &str) -> Result<Output, io::Error> {
+        server
+            .command()
+            .args(&["list-windows", "-t", target, "-F", LIST_FORMAT])
+            .output()
     }
 }
 
@@ -90,86 +96,80 @@ impl Serialize for Window {
 mod test {
     use super::*;
 
-    #[test] // Window with name* representing the active window.
-    fn expect_some_from_active_window_line() {
-        let line = "2: vim* (1 panes) [layout b5be,173x42,0,0,1]";
-        let window = Window::from_line(line);
-        assert!(window.is_some())
-    }
-
-    #[test] // Window with name- representing the previous active window.
-    fn expect_some_from_previous_window_line() {
-        let line = "2: vim-  (1 panes) [layout b5be,173x42,0,0,1]";
-        let window = Window::from_line(line);
-        assert!(window.is_some())
-    }
-
-    #[test] // Window with name and no designation.
+    #[test]
     fn expect_some_from_window_line() {
-        let line = "2: vim  (1 panes) [layout b5be,173x42,0,0,1]";
-        let window = Window::from_line(line);
-        assert!(window.is_some())
-    }
-
-    #[test] // Window with no name and active.
-    fn expect_some_from_active_window_blank_name() {
-        let line = "2: * (1 panes) [layout b5be,173x42,0,0,1]";
-        let window = Window::from_line(line);
-        assert!(window.is_some())
-    }
-
-    #[test] // Window with no name and the previous active window.
-    fn expect_some_from_previous_window_blank_name() {
-        let line = "2: - (1 panes) [layout b5be,173x42,0,0,1]";
+        let line = "@2\t2\t0\tb5be,173x42,0,0,1\tvim";
         let window = Window::from_line(line);
         assert!(window.is_some())
     }
 
-    #[test] // Window with blank name
+    #[test]
     fn expect_some_with_blank_name() {
-        let line = "2:   (1 panes) [layout b5be,173x42,0,0,1]";
-        let window = Window::from_line(line);
-        assert!(window.is_some())
+        let line = "@2\t2\t0\tb5be,173x42,0,0,1\t";
+        let window = Window::from_line(line).unwrap();
+        assert_eq!(window.name, "")
     }
 
     #[test]
-    fn expect_none_from_line_missing_name() {
-        let line = "2: (1 panes) [layout b5be,173x42,0,0,1]";
+    fn expect_none_from_line_missing_fields() {
+        let line = "@2\t2\t0";
         let window = Window::from_line(line);
         assert!(window.is_none())
     }
 
     #[test]
-    fn expect_some_from_line_with_dollar_sign() {
-        let line = "2: vim$ (1 panes) [layout b5be,173x42,0,0,1]";
+    fn expect_none_from_line_without_id() {
+        let line = "2: vim* (1 panes) [layout b5be,173x42,0,0,1]";
         let window = Window::from_line(line);
-        assert!(window.is_some())
+        assert!(window.is_none())
     }
 
     #[test]
     fn expect_active_to_be_true() {
-        let line = "2: vim* (1 panes) [layout b5be,173x42,0,0,1]";
+        let line = "@2\t2\t1\tb5be,173x42,0,0,1\tvim";
         let window = Window::from_line(line).unwrap();
         assert!(window.active)
     }
 
     #[test]
-    fn expect_active_to_be_true_without_name() {
-        let line = "2: * (1 panes) [layout b5be,173x42,0,0,1]";
+    fn expect_active_to_be_false() {
+        let line = "@2\t2\t0\tb5be,173x42,0,0,1\tvim";
         let window = Window::from_line(line).unwrap();
-        assert!(window.active)
+        assert!(!window.active)
     }
 
     #[test]
     fn expect_name_to_be_vim() {
-        let line = "2: vim* (1 panes) [layout b5be,173x42,0,0,1]";
+        let line = "@2\t2\t1\tb5be,173x42,0,0,1\tvim";
         let window = Window::from_line(line).unwrap();
         assert_eq!(window.name, "vim")
     }
 
+    #[test]
+    fn expect_name_with_punctuation_and_spaces() {
+        let line = "@2\t2\t1\tb5be,173x42,0,0,1\tmy-app.rb: logs (dev)";
+        let window = Window::from_line(line).unwrap();
+        assert_eq!(window.name, "my-app.rb: logs (dev)")
+    }
+
+    #[test]
+    fn expect_name_with_a_tab() {
+        let line = "@2\t2\t1\tb5be,173x42,0,0,1\tleft\tright";
+        let window = Window::from_line(line).unwrap();
+        assert_eq!(window.name, "left\tright")
+    }
+
+    #[test]
+    fn expect_id_and_index() {
+        let line = "@7\t3\t1\tb5be,173x42,0,0,1\tvim";
+        let window = Window::from_line(line).unwrap();
+        assert_eq!(window.id, "@7");
+        assert_eq!(window.index, 3)
+    }
+
     #[test]
     fn expect_layout_to_match() {
-        let line = "2: vim* (1 panes) [layout b5be,173x42,0,0,1]";
+        let line = "@2\t2\t1\tb5be,173x42,0,0,1\tvim";
         let window = Window::from_line(line).unwrap();
         assert_eq!(window.layout, "b5be,173x42,0,0,1")
     }
@@ -1,7 +1,9 @@
 //! Muxed. A tmux project manager with no runtime dependencies.
+extern crate autosave;
 extern crate common;
 extern crate docopt;
 extern crate edit;
+extern crate import;
 extern crate load;
 extern crate new;
 extern crate snapshot;
@@ -26,31 +28,83 @@ macro_rules! try_or_err (
 
 static USAGE: &str = "
 Usage:
-    muxed [options] <project>
     muxed edit [options] <project>
     muxed new [options] <project>
-    muxed snapshot [options] <project>
+    muxed snapshot [options] --all <dir>
+    muxed snapshot [options] [<project>]
+    muxed autosave [options]
+    muxed restore [options] [--all | <project>]
+    muxed import [options] <format> <file>
+    muxed list [options]
+    muxed pick [options]
+    muxed recent [options]
+    muxed last [options]
+    muxed record [options] <project>
+    muxed restart [options] <pane>
+    muxed run [options] <project> <target> [--] <command>...
+    muxed exec [options] [--all | --window <window> | --tag <tag>] <project> [--] <command>...
+    muxed [options] [<project>]
     muxed (-h | --help)
     muxed (-v | --version)
 
 Flags:
-    -d                  If you want to create a muxed session without connecting to it
-    -f                  Overwrite existing file if one exists
-    --debug             Prints debug information while executing (project opening only)
-    -h, --help          Prints help information
-    -v, --version       Prints version information
+    --all                   Snapshot every running session, restore every saved one, or exec in every pane
+    --confirm               List the panes exec sends a command to, and ask before sending it
+    -d                      If you want to create a muxed session without connecting to it
+    -f                      Overwrite existing file if one exists
+    --debug                 Prints debug information while executing (project opening only)
+    -h, --help              Prints help information
+    --hooks                 Set tmux hooks autosaving whenever a session changes, instead of on an interval
+    --once                  Autosave once and exit
+    -v, --version           Prints version information
+    --wait                  Wait for the command sent with run to finish, and exit with its status
+    --with-contents         Save pane contents with a snapshot, and replay them when opening a project
 
 Options:
-    -p <project_dir>    The directory your project config files live in. Defaults to ~/.muxed/
-    -t <session>        The name of the running TMUX session to codify
+    -p <project_dir>        The directory your project config files live in. Defaults to ~/.muxed/
+    --allow <commands>      Comma separated programs a snapshot should restore. Defaults to all
+    --deny <commands>       Comma separated programs a snapshot should not restore, besides shells
+    --history <lines>       Lines of each pane's history saved --with-contents. Defaults to 2000
+    --interval <seconds>    Seconds between autosaves. Defaults to 300
+    --keep <saves>          How many autosaves to keep. Defaults to 10
+    --nested <mode>         How to attach when run from inside of tmux: switch, new or print. Defaults to switch
+    --output <file>         The asciicast file to record to. Defaults to <project>.cast
+    --socket-name <name>    Use the tmux server on a named socket (tmux -L)
+    --socket-path <path>    Use the tmux server on a socket path (tmux -S)
+    --tmux-config <file>    Start the tmux server with this config file (tmux -f)
+    --tmux-command <cmd>    The tmux program to call. Defaults to tmux
+    -t <session>            The name of the running TMUX session to codify
+    --tag <tag>             Send exec's command only to the panes and windows with this tag
+    --window <window>       Send exec's command only to this window, or <window>.<pane>
+    --worktree <branch>     Open the project in a git worktree of <branch>, as a session of its own
 
 Args:
-    <project>           The name of your project to open
+    <project>           The name of your project to open. Left out, pick one from a list
+    <dir>               The directory to snapshot every running session in to, one project file each
+    <format>            The tool a project file comes from: tmuxinator, tmuxp, teamocil or procfile
+    <file>              The project file to import
+    <pane>              The pane to restart, ex. %3
+    <target>            The window to run in, with .<pane> for one of its panes, ex. server.1
+    <command>           The command to run, after --
 
 Subcommands:
     edit <project>                   Edit an existing project file
     new <project>                    To create a new project file
-    snapshot -t <session> <project>  Capture a running session and create a config file for it
+    snapshot -t <session> <project>  Capture a running session and create a config file for it.
+                                     Defaults to the current session, named after itself
+    autosave                         Keep every running session saved in <project_dir>/autosave/
+    restore [--all | <project>]      Recreate sessions from the latest autosave, all of them by default
+    import <format> <file>           Convert another tool's project file in to a muxed project,
+                                     named after the file
+    list                             Every project, with its running sessions and worktrees
+    pick                             Choose a project to open from a list, filtered as you type
+    recent                           The projects opened lately, newest first
+    last                             Open the project opened last again, with the same options
+    record <project>                 Open a project and record the attached client to an asciicast file
+    restart <pane>                   Start a pane set to `restart` again. Run by tmux when it exits
+    run <project> <target>           Type <command> in to a window or pane of a running project
+    exec <project>                   Type <command> in to every pane of a running project, or those picked
+                                     with --window or --tag
 ";
 
 /// The main execution method.
@@ -92,7 +146,21 @@ pub fn main() {
             "edit" => try_or_err!(edit::exec(args)),
             "new" => try_or_err!(new::exec(args)),
             "snapshot" => try_or_err!(snapshot::exec(args)),
+            "autosave" => try_or_err!(autosave::exec(args)),
+            "restore" => try_or_err!(autosave::restore(args)),
+            "import" => try_or_err!(import::exec(args)),
+            "list" => try_or_err!(load::list::exec(args)),
+            "pick" => try_or_err!(load::pick::exec(args)),
+            "recent" => try_or_err!(load::history::recent(args)),
+            "last" => try_or_err!(load::history::last(args)),
+            "record" => try_or_err!(load::record::exec(args)),
+            "restart" => try_or_err!(load::restart::exec(args)),
+            "run" => exit(try_or_err!(load::run::exec(args))),
+            "exec" => try_or_err!(load::broadcast::exec(args)),
+            _ if args.arg_project.is_empty() => try_or_err!(load::pick::exec(args)),
             _ => try_or_err!(load::exec(args)),
         }
+    } else {
+        try_or_err!(load::pick::exec(args))
     }
 }